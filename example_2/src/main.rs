@@ -43,18 +43,33 @@ struct SubProps {
 #[function_component(Sub)]
 fn sub(props: &SubProps) -> Html {
     let sub_routes = sub_routes();
+
+    html! {
+        <Switch
+            routes={ sub_routes.clone() }
+            render={ switch_sub }
+            layout={ Callback::from(sub_layout) }
+            pathname={ props.sub_path.clone() }
+        />
+    }
+}
+
+#[function_component(GoHome)]
+fn go_home() -> Html {
     let navigator = use_navigator().unwrap();
 
-    let onclick_callback = Callback::from(move |_| navigator.push("../"));
+    let onclick_callback = Callback::from(move |_| navigator.report(navigator.push("../")));
+    html! {
+        <button onclick={ onclick_callback }>{ "Go Home" }</button>
+    }
+}
+
+fn sub_layout(_: RouteOutput) -> Html {
     html! {
         <div>
             <h2>{ "Sub Component" }</h2>
-            <Switch
-                routes={ sub_routes.clone() }
-                render={ switch_sub }
-                pathname={ props.sub_path.clone() }
-            />
-            <button onclick={ onclick_callback }>{ "Go Home" }</button>
+            <Outlet />
+            <GoHome />
             <h2>{ "Sub Component End" }</h2>
         </div>
     }
@@ -85,14 +100,21 @@ fn main() -> Html {
         pathname
     };
 
+    html! {
+        <Switch
+            routes={ routes }
+            render={ switch_main }
+            layout={ Callback::from(main_layout) }
+            pathname={ pathname }
+        />
+    }
+}
+
+fn main_layout(_: RouteOutput) -> Html {
     html! {
         <div>
             <h1>{ "Main Component" }</h1>
-            <Switch
-                routes={ routes }
-                render={ switch_main }
-                pathname={ pathname }
-            />
+            <Outlet />
             <h1>{ "Main Component End" }</h1>
         </div>
     }
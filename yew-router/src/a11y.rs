@@ -0,0 +1,102 @@
+//! Route announcements and focus management.
+//!
+//! With `announce` set, a router renders a visually hidden ARIA live region and announces the
+//! new page after each navigation. With `focus` set, it moves focus to the element matching the
+//! selector, e.g. `main` or `#content`. Routes override both with [`RouteMeta::announcement`] and
+//! [`RouteMeta::focus`].
+//!
+//! Each navigation is announced and focused once, after the document head is updated. Redirects
+//! do not do it again, and neither the page a user lands on nor its redirects do it at all.
+
+use gloo::timers::callback::Timeout;
+use wasm_bindgen::JsCast;
+use yew::prelude::*;
+use yew::virtual_dom::AttrValue;
+
+use crate::head::RouteMeta;
+
+// Hidden visually but still read by screen readers.
+const VISUALLY_HIDDEN: &str = "position:absolute;width:1px;height:1px;margin:-1px;padding:0;\
+                               overflow:hidden;clip:rect(0,0,0,0);white-space:nowrap;border:0;";
+
+// How long the live region stays empty before a message is written to it.
+const ANNOUNCE_DELAY_MS: u32 = 100;
+
+/// Returns the text announced for a page with `meta`, falling back to the document title.
+pub(crate) fn announcement(meta: Option<&RouteMeta>, title_template: Option<&str>) -> String {
+    meta.and_then(|meta| {
+        meta.announcement
+            .clone()
+            .or_else(|| meta.full_title(title_template))
+    })
+    .unwrap_or_else(|| gloo::utils::document().title())
+}
+
+/// Focuses the first element matching `selector`.
+///
+/// Elements that are not focusable get `tabindex="-1"`, so they can be focused without being
+/// added to the tab order.
+pub(crate) fn move_focus(selector: &str) {
+    let element = match gloo::utils::document().query_selector(selector) {
+        Ok(Some(element)) => element,
+        _ => {
+            tracing::warn!(selector, "no element to focus");
+            return;
+        }
+    };
+
+    if !element.has_attribute("tabindex") {
+        let _ = element.set_attribute("tabindex", "-1");
+    }
+    if let Ok(element) = element.dyn_into::<web_sys::HtmlElement>() {
+        let _ = element.focus();
+    }
+}
+
+#[derive(Properties, PartialEq, Clone)]
+pub(crate) struct LiveRegionProps {
+    /// Changes for every announcement, so that the same message is announced again.
+    pub id: u32,
+    pub message: AttrValue,
+}
+
+/// The live region announcements are rendered into.
+///
+/// The region is cleared before each message is written, as screen readers only announce
+/// changes of its content.
+#[function_component(LiveRegion)]
+pub(crate) fn live_region(props: &LiveRegionProps) -> Html {
+    let region = use_node_ref();
+    let timeout = use_mut_ref(|| None::<Timeout>);
+
+    {
+        let region = region.clone();
+
+        use_effect_with_deps(
+            move |(_, message)| {
+                if let Some(element) = region.get() {
+                    element.set_text_content(None);
+
+                    let message = message.clone();
+                    // Replacing a pending write cancels it.
+                    *timeout.borrow_mut() = Some(Timeout::new(ANNOUNCE_DELAY_MS, move || {
+                        element.set_text_content(Some(&message));
+                    }));
+                }
+
+                || {}
+            },
+            (props.id, props.message.clone()),
+        );
+    }
+
+    html! {
+        <div
+            ref={region}
+            role="status"
+            aria-live="polite"
+            aria-atomic="true"
+            style={VISUALLY_HIDDEN}
+        />
+    }
+}
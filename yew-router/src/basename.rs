@@ -0,0 +1,143 @@
+//! Parameterized basenames.
+//!
+//! A basename may contain `:name` segments, e.g. `/t/:tenant`, to host several tenants under one
+//! router. The basename is resolved against each location, so `/t/acme/settings` has the
+//! basename `/t/acme` and the route `/settings`, and switching the tenant only pushes a new
+//! location, see [`Navigator::push_basename`](crate::navigator::Navigator::push_basename).
+//!
+//! Outside of a parameterized basename, e.g. on a login page next to the tenants, the values of
+//! its segments are unknown. Navigating to a route there fails with
+//! [`NavigationError::Unsupported`](crate::navigator::NavigationError::Unsupported) instead of
+//! guessing them; `push_basename` enters the basename with the params given.
+
+use std::collections::BTreeMap;
+
+use crate::utils::{decode_segment, fill_params, strip_slash_suffix};
+
+/// The basename of a router, either static or with `:name` segments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basename {
+    pattern: String,
+}
+
+/// A [`Basename`] resolved against a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedBasename {
+    /// The prefix of the path matched by the basename.
+    pub prefix: String,
+    /// The decoded values of the `:name` segments.
+    pub params: BTreeMap<String, String>,
+}
+
+impl Basename {
+    /// Creates a basename from `pattern`, a trailing slash is ignored.
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: strip_slash_suffix(pattern).to_string(),
+        }
+    }
+
+    /// Returns the pattern of the basename.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns whether the basename has `:name` segments.
+    pub fn is_dynamic(&self) -> bool {
+        self.pattern
+            .split('/')
+            .any(|segment| segment.starts_with(':'))
+    }
+
+    /// Resolves the basename against `path`, `None` if `path` does not start with it.
+    pub fn resolve(&self, path: &str) -> Option<ResolvedBasename> {
+        let span = tracing::trace_span!(
+            "resolve_basename",
+            pattern = %self.pattern,
+            path,
+            prefix = tracing::field::Empty,
+        )
+        .entered();
+        let mut params = BTreeMap::new();
+        let mut path_segments = path.split('/');
+        let mut len = 0;
+
+        for (index, segment) in self.pattern.split('/').enumerate() {
+            let value = path_segments.next()?;
+            match segment.strip_prefix(':') {
+                Some(_) if value.is_empty() => return None,
+                Some(name) => {
+                    params.insert(name.to_string(), decode_segment(value).into_owned());
+                }
+                None if segment != value => return None,
+                None => {}
+            }
+            // The separators between segments are part of the prefix.
+            len += value.len() + usize::from(index > 0);
+        }
+
+        let prefix = &path[..len];
+        span.record("prefix", prefix);
+
+        Some(ResolvedBasename {
+            prefix: prefix.to_string(),
+            params,
+        })
+    }
+
+    /// Substitutes the `:name` segments with `params`, `None` if one of them is missing.
+    pub fn fill(&self, params: &BTreeMap<String, String>) -> Option<String> {
+        let missing = self
+            .pattern
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix(':'))
+            .any(|name| !params.contains_key(name));
+
+        if missing {
+            None
+        } else {
+            Some(fill_params(&self.pattern, params))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_basename() {
+        let basename = Basename::new("/app/");
+        assert_eq!(basename.pattern(), "/app");
+        assert!(!basename.is_dynamic());
+
+        let resolved = basename.resolve("/app/settings").unwrap();
+        assert_eq!(resolved.prefix, "/app");
+        assert!(resolved.params.is_empty());
+        assert_eq!(basename.resolve("/other/settings"), None);
+    }
+
+    #[test]
+    fn dynamic_basename() {
+        let basename = Basename::new("/t/:tenant");
+        assert!(basename.is_dynamic());
+
+        let resolved = basename.resolve("/t/gr%C3%BC%C3%9Fe/settings").unwrap();
+        assert_eq!(resolved.prefix, "/t/gr%C3%BC%C3%9Fe");
+        assert_eq!(
+            resolved.params.get("tenant").map(String::as_str),
+            Some("grüße")
+        );
+        assert_eq!(basename.resolve("/t/"), None);
+        assert_eq!(basename.resolve("/login"), None);
+    }
+
+    #[test]
+    fn fill_dynamic_basename() {
+        let basename = Basename::new("/t/:tenant");
+
+        let params = BTreeMap::from([("tenant".to_string(), "a/b".to_string())]);
+        assert_eq!(basename.fill(&params), Some("/t/a%2Fb".to_string()));
+        assert_eq!(basename.fill(&BTreeMap::new()), None);
+    }
+}
@@ -1,10 +1,13 @@
+use std::collections::BTreeMap;
+
 use serde::Serialize;
 use wasm_bindgen::UnwrapThrowExt;
 use yew::prelude::*;
 use yew::virtual_dom::AttrValue;
 
-use crate::navigator::NavigatorKind;
 use crate::prelude::*;
+use crate::state::BackgroundState;
+use crate::url::UrlBuilder;
 use crate::utils;
 
 /// Props for [`Link`]
@@ -18,11 +21,26 @@ where
     pub classes: Classes,
     /// Route that will be pushed when the anchor is clicked.
     pub to: String,
+    /// Values for the `:name` segments of `to`, percent-encoded when the path is built.
+    #[prop_or_default]
+    pub params: BTreeMap<String, String>,
     /// Route query data
     #[prop_or_default]
     pub query: Option<Q>,
+    /// Fragment to scroll to, without the leading `#`.
+    ///
+    /// Linking to a fragment of the current page scrolls to it without re-rendering the route.
+    #[prop_or_default]
+    pub fragment: Option<String>,
     #[prop_or_default]
     pub disabled: bool,
+    /// Open the route as a modal over the current page.
+    ///
+    /// The current location, or the background of the current location if it is a modal itself,
+    /// becomes the background location of the new entry. See
+    /// [`use_background_location`](crate::hooks::use_background_location).
+    #[prop_or_default]
+    pub modal: bool,
     /// [`NodeRef`](yew::html::NodeRef) for the `<a>` element.
     #[prop_or_default]
     pub anchor_ref: NodeRef,
@@ -39,48 +57,66 @@ where
     let LinkProps {
         classes,
         to,
+        params,
         children,
         disabled,
+        modal,
         query,
+        fragment,
         anchor_ref,
     } = props.clone();
 
+    let to = if params.is_empty() {
+        to
+    } else {
+        utils::fill_params(&to, &params)
+    };
+    let to = match fragment {
+        Some(fragment) => UrlBuilder::new(&to).fragment(&fragment).build(),
+        None => to,
+    };
+
     let navigator = use_navigator().expect_throw("failed to get navigator");
+    let location = use_location();
+    let background = use_background_location();
 
     let onclick = {
         let navigator = navigator.clone();
         let to = to.clone();
         let query = query.clone();
+        let background = if modal { background.or(location) } else { None };
 
         Callback::from(move |e: MouseEvent| {
             e.prevent_default();
 
-            match query {
-                None => {
-                    navigator.push(&to);
-                }
-                Some(ref data) => {
-                    navigator
-                        .push_with_query(&to, data)
-                        .expect_throw("failed push history with query");
+            let result = match (&query, background.clone()) {
+                (None, None) => navigator.push(&to),
+                (None, Some(background)) => navigator.push_with_background(&to, background),
+                (Some(data), None) => navigator.push_with_query(&to, data),
+                (Some(data), Some(background)) => {
+                    navigator.push_with_query_and_state(&to, data, BackgroundState(background))
                 }
-            }
+            };
+            navigator.report(result);
         })
     };
 
+    // A query that does not serialize, or a basename that can not be filled in, leaves the link
+    // without a `href`, the error is reported when it is clicked.
     let href = {
-        let route_s = to;
-        let pathname = navigator.prefix_basename(&route_s);
-        let mut path = query
-            .and_then(|query| serde_urlencoded::to_string(query).ok())
-            .and_then(|query| utils::compose_path(&pathname, &query))
-            .unwrap_or_else(|| pathname.into_owned());
+        let url = UrlBuilder::new(&to);
+        let url = match query {
+            Some(query) => url.query(&query),
+            None => Ok(url),
+        };
 
-        if navigator.kind() == NavigatorKind::Hash {
-            path.insert(0, '#');
+        match url.and_then(|url| navigator.href_for(&url)) {
+            Ok(href) => Some(AttrValue::from(href)),
+            Err(e) => {
+                tracing::warn!(error = %e, to = %to, "link has no href");
+                None
+            }
         }
-
-        AttrValue::from(path)
     };
 
     html! {
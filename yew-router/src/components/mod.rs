@@ -1,6 +1,15 @@
 //! Components to interface with [Router][crate::Router].
 
 mod link;
+mod outlet;
+mod protected;
 mod redirect;
+mod transition;
 pub use link::*;
+pub(crate) use outlet::OutletContext;
+pub use outlet::*;
+pub(crate) use protected::return_to;
+pub use protected::*;
 pub use redirect::*;
+pub(crate) use transition::TransitionPhase;
+pub use transition::*;
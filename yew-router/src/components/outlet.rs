@@ -0,0 +1,18 @@
+use yew::prelude::*;
+
+/// The matched child route handed from a [`Switch`](crate::Switch) to its layout.
+#[derive(Clone, PartialEq)]
+pub(crate) struct OutletContext {
+    pub html: Html,
+}
+
+/// A component that renders the route matched by the [`Switch`](crate::Switch) whose `layout` it
+/// is rendered in.
+///
+/// Nothing is rendered outside of a layout.
+#[function_component(Outlet)]
+pub fn outlet() -> Html {
+    use_context::<OutletContext>()
+        .map(|ctx| ctx.html)
+        .unwrap_or_default()
+}
@@ -0,0 +1,221 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use serde::Serialize;
+use wasm_bindgen::UnwrapThrowExt;
+use yew::prelude::*;
+
+use crate::components::Redirect;
+use crate::hooks::{use_location, use_navigator};
+
+/// The query param holding the route a [`Protected`] redirected away from.
+pub const RETURN_TO_QUERY_KEY: &str = "return_to";
+
+type AuthorizeFuture = Pin<Box<dyn Future<Output = bool>>>;
+
+/// An async predicate deciding whether the current user may see a [`Protected`] subtree.
+///
+/// Two values are equal only if they are clones of each other, and [`Protected`] runs the
+/// predicate again whenever the value changes. Create it once per component, usually with
+/// `use_memo`, instead of on every render:
+///
+/// ```ignore
+/// let authorize = use_memo(
+///     |session| {
+///         let session = session.clone();
+///         Authorize::new(move || is_admin(session.clone()))
+///     },
+///     session,
+/// );
+///
+/// html! {
+///     <Protected authorize={(*authorize).clone()} login="/login">
+///         <Settings />
+///     </Protected>
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Authorize(Rc<dyn Fn() -> AuthorizeFuture>);
+
+impl Authorize {
+    pub fn new<F, Fut>(f: F) -> Self
+    where
+        F: Fn() -> Fut + 'static,
+        Fut: Future<Output = bool> + 'static,
+    {
+        Self(Rc::new(move || Box::pin(f()) as AuthorizeFuture))
+    }
+}
+
+impl PartialEq for Authorize {
+    fn eq(&self, rhs: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &rhs.0)
+    }
+}
+
+impl fmt::Debug for Authorize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authorize").finish()
+    }
+}
+
+/// Props for [`Protected`]
+#[derive(Properties, Clone, PartialEq)]
+pub struct ProtectedProps {
+    /// Decides whether `children` are rendered. Must be memoized, see [`Authorize`].
+    pub authorize: Authorize,
+    /// Route of the login page unauthorized users are redirected to.
+    pub login: String,
+    /// Rendered until `authorize` resolves for the first time.
+    #[prop_or_default]
+    pub fallback: Html,
+    pub children: Children,
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+struct ReturnTo {
+    return_to: String,
+}
+
+/// A component that renders its children only if `authorize` resolves to `true`.
+///
+/// Otherwise the current entry is replaced with the `login` route, whose `return_to` query param
+/// holds the route the user tried to open. After logging in,
+/// [`Navigator::resume_after_login`](crate::navigator::Navigator::resume_after_login) navigates
+/// back to it.
+///
+/// `authorize` is run again whenever it changes. The previous result is kept while it is
+/// pending, so the children stay mounted instead of falling back to `fallback`.
+#[function_component(Protected)]
+pub fn protected(props: &ProtectedProps) -> Html {
+    let navigator = use_navigator().expect_throw("failed to read history.");
+    let location = use_location().expect_throw("failed to read location.");
+    let authorized = use_state(|| None::<bool>);
+    // Only the latest run of `authorize` may settle the state.
+    let generation = use_mut_ref(|| 0_u32);
+    // The generation of the last result.
+    let settled = use_state(|| 0_u32);
+    // Awaited navigations settle once `authorize` has resolved, either here or at the login route.
+    let hold = use_mut_ref(|| None);
+
+    {
+        let authorized = authorized.clone();
+        let settled = settled.clone();
+        let generation = generation.clone();
+        let hold = hold.clone();
+        let hub = navigator.hub().clone();
+
+        use_effect_with_deps(
+            move |authorize| {
+                *generation.borrow_mut() += 1;
+                let current = *generation.borrow();
+                *hold.borrow_mut() = Some(hub.hold());
+
+                let future = (authorize.0)();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let result = future.await;
+                    if *generation.borrow() == current {
+                        authorized.set(Some(result));
+                        settled.set(current);
+                    }
+                });
+
+                || {}
+            },
+            props.authorize.clone(),
+        );
+    }
+
+    // Runs after the effect of a `Redirect` to the login route, which continues the navigation.
+    use_effect_with_deps(
+        move |settled| {
+            if *settled == *generation.borrow() {
+                hold.borrow_mut().take();
+            }
+            || {}
+        },
+        *settled,
+    );
+
+    match *authorized {
+        Some(true) => html! { <>{ props.children.clone() }</> },
+        Some(false) => {
+            let return_to = format!(
+                "{}{}{}",
+                navigator.strip_basename(location.path()),
+                location.query_str(),
+                location.hash()
+            );
+            tracing::debug!(login = %props.login, return_to = %return_to, "not authorized");
+
+            html! {
+                <Redirect<ReturnTo> to={props.login.clone()} query={ReturnTo { return_to }} />
+            }
+        }
+        None => props.fallback.clone(),
+    }
+}
+
+/// Returns the `return_to` query param of `query_str` if it is a path of this application.
+///
+/// Anything else, such as `//evil.example` or `https://evil.example`, is rejected so that the
+/// param cannot be used for an open redirect.
+pub(crate) fn return_to(query_str: &str) -> Option<String> {
+    let query = query_str.strip_prefix('?').unwrap_or(query_str);
+    let pairs: Vec<(String, String)> = serde_urlencoded::from_str(query).ok()?;
+    let (_, path) = pairs
+        .into_iter()
+        .find(|(key, _)| key == RETURN_TO_QUERY_KEY)?;
+
+    if path.starts_with('/') && !path.starts_with("//") && !path.contains('\\') {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(return_to: &str) -> String {
+        let query = serde_urlencoded::to_string(ReturnTo {
+            return_to: return_to.to_string(),
+        })
+        .unwrap();
+        format!("?{}", query)
+    }
+
+    #[test]
+    fn accepts_paths_of_the_application() {
+        assert_eq!(
+            return_to(&query("/path?q#f")),
+            Some("/path?q#f".to_string())
+        );
+        assert_eq!(
+            return_to("?tab=1&return_to=%2Fsettings"),
+            Some("/settings".to_string())
+        );
+        assert_eq!(return_to(&query("/")), Some("/".to_string()));
+    }
+
+    #[test]
+    fn rejects_other_origins() {
+        for target in [
+            "//evil.com",
+            "/\\evil.com",
+            "https://evil.com",
+            "evil.com",
+            "",
+        ] {
+            assert_eq!(return_to(&query(target)), None, "{target:?}");
+        }
+        assert_eq!(return_to("?return_to=%2F%2Fevil.com"), None);
+        assert_eq!(return_to("?return_to=%2F%5Cevil.com"), None);
+        assert_eq!(return_to("?return_to=%252F%252Fevil.com"), None);
+        assert_eq!(return_to("?return_to="), None);
+        assert_eq!(return_to(""), None);
+    }
+}
@@ -1,26 +1,81 @@
+use serde::Serialize;
 use wasm_bindgen::UnwrapThrowExt;
 use yew::prelude::*;
 
-use crate::hooks::use_navigator;
+use crate::hooks::{use_location, use_navigator};
+use crate::utils::resolve_path;
 
 /// Props for [`Redirect`]
-#[derive(Properties, Clone, PartialEq, Eq)]
-pub struct RedirectProps {
-    /// Route that will be pushed when the component is rendered.
+#[derive(Properties, Clone, PartialEq)]
+pub struct RedirectProps<Q = (), S = ()>
+where
+    Q: Clone + PartialEq + Serialize,
+    S: Clone + PartialEq,
+{
+    /// Route that will be navigated to when the component is rendered.
+    ///
+    /// A relative route, such as `../login`, is resolved against the current location.
     pub to: String,
+    /// Route query data
+    #[prop_or_default]
+    pub query: Option<Q>,
+    /// State associated with the new history entry.
+    #[prop_or_default]
+    pub state: Option<S>,
+    /// Push a new history entry instead of replacing the current one.
+    #[prop_or_default]
+    pub push: bool,
 }
 
 /// A component that will redirect to specified route when rendered.
+///
+/// The current history entry is replaced by default, so the back button does not lead to the
+/// redirect again. The redirect happens once for every change of its props.
 #[function_component(Redirect)]
-pub fn redirect(props: &RedirectProps) -> Html {
-    let history = use_navigator().expect_throw("failed to read history.");
+pub fn redirect<Q = (), S = ()>(props: &RedirectProps<Q, S>) -> Html
+where
+    Q: Clone + PartialEq + Serialize + 'static,
+    S: Clone + PartialEq + 'static,
+{
+    // Continues the navigation that rendered the redirect, if it is still in progress.
+    let navigator = use_navigator()
+        .expect_throw("failed to read history.")
+        .redirecting();
+    let location = use_location().expect_throw("failed to read location.");
 
-    let target_route = props.to.clone();
-    use_effect(move || {
-        history.push(&target_route);
+    let RedirectProps {
+        to,
+        query,
+        state,
+        push,
+    } = props.clone();
 
-        || {}
-    });
+    use_effect_with_deps(
+        move |(to, query, state, push)| {
+            let path = navigator.strip_basename(location.path());
+            let to = resolve_path(&path, to);
+            tracing::debug!(to = %to, push = *push, "redirecting");
+
+            let result = match (query, state.clone(), *push) {
+                (None, None, false) => navigator.replace(&to),
+                (None, None, true) => navigator.push(&to),
+                (None, Some(state), false) => navigator.replace_with_state(&to, state),
+                (None, Some(state), true) => navigator.push_with_state(&to, state),
+                (Some(query), None, false) => navigator.replace_with_query(&to, query),
+                (Some(query), None, true) => navigator.push_with_query(&to, query),
+                (Some(query), Some(state), false) => {
+                    navigator.replace_with_query_and_state(&to, query, state)
+                }
+                (Some(query), Some(state), true) => {
+                    navigator.push_with_query_and_state(&to, query, state)
+                }
+            };
+            navigator.report(result);
+
+            || {}
+        },
+        (to, query, state, push),
+    );
 
     Html::default()
 }
@@ -0,0 +1,136 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo::timers::callback::Timeout;
+use wasm_bindgen::UnwrapThrowExt;
+use yew::prelude::*;
+use yew::virtual_dom::AttrValue;
+
+use crate::events::NavigationAction;
+use crate::router::LocationContext;
+
+/// Props for [`RouteTransition`]
+#[derive(Properties, Clone, PartialEq)]
+pub struct RouteTransitionProps {
+    /// How long the outgoing route stays mounted, in milliseconds.
+    #[prop_or(300)]
+    pub duration: u32,
+    /// Prefix of the CSS classes set on the wrappers.
+    #[prop_or_else(|| AttrValue::from("route"))]
+    pub class_prefix: AttrValue,
+    pub children: Children,
+}
+
+/// Provided around every route of a [`RouteTransition`]. Switches in the subtree of an outgoing
+/// route must not act on the current location.
+///
+/// It is provided for the current route too, so that the subtree is not remounted when it starts
+/// exiting.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct TransitionPhase {
+    pub exiting: bool,
+}
+
+struct TransitionState {
+    ctr: u32,
+    children: Children,
+    outgoing: Option<(u32, Children)>,
+    direction: &'static str,
+    timeout: Option<Timeout>,
+}
+
+/// A wrapper around a [`Switch`](crate::Switch) that animates route changes.
+///
+/// After a navigation the previous children stay mounted for `duration` milliseconds next to the
+/// new ones. With the default prefix the wrappers get these classes:
+///
+/// - `route-exit` on the outgoing route and `route-enter` on the incoming one during the
+///   transition, `route-current` on the route once it is over.
+/// - `route-forward` for a push or replace and `route-back` for a pop, on both wrappers.
+///
+/// A navigation in the middle of a transition cancels it: the route that was entering becomes
+/// the outgoing one and the timer starts over.
+#[function_component(RouteTransition)]
+pub fn route_transition(props: &RouteTransitionProps) -> Html {
+    let loc_ctx = use_context::<LocationContext>().expect_throw("failed to read location.");
+    let state = use_mut_ref(|| TransitionState {
+        ctr: loc_ctx.ctr(),
+        children: props.children.clone(),
+        outgoing: None,
+        direction: "forward",
+        timeout: None,
+    });
+    let rerender = use_state(|| 0_u32);
+    // Routes of a transition nested in an outgoing route are outgoing as well.
+    let parent_exiting = use_context::<TransitionPhase>().map_or(false, |phase| phase.exiting);
+    let entering = TransitionPhase {
+        exiting: parent_exiting,
+    };
+
+    let mut current = state.borrow_mut();
+    if current.ctr != loc_ctx.ctr() {
+        let previous = std::mem::replace(&mut current.children, props.children.clone());
+        let previous_ctr = std::mem::replace(&mut current.ctr, loc_ctx.ctr());
+
+        // Replacing a running transition drops its timeout, which cancels it.
+        current.outgoing = Some((previous_ctr, previous));
+        current.direction = match loc_ctx.navigation().action {
+            NavigationAction::Pop => "back",
+            NavigationAction::Push | NavigationAction::Replace => "forward",
+        };
+        current.timeout = Some({
+            let state = Rc::downgrade(&state);
+            let rerender = rerender.clone();
+
+            Timeout::new(props.duration, move || {
+                if let Some(state) = state.upgrade() {
+                    let mut state = RefCell::borrow_mut(&state);
+                    state.outgoing = None;
+                    state.timeout = None;
+                }
+                rerender.set(*rerender + 1);
+            })
+        });
+    } else {
+        current.children = props.children.clone();
+    }
+
+    let prefix = &props.class_prefix;
+    let direction = format!("{}-{}", prefix, current.direction);
+
+    match &current.outgoing {
+        Some((outgoing_ctr, outgoing)) => html! {
+            <>
+                <div
+                    key={outgoing_ctr.to_string()}
+                    class={classes!(format!("{}-exit", prefix), direction.clone())}
+                    aria-hidden="true"
+                >
+                    <ContextProvider<TransitionPhase> context={TransitionPhase { exiting: true }}>
+                        { outgoing.clone() }
+                    </ContextProvider<TransitionPhase>>
+                </div>
+                <div
+                    key={current.ctr.to_string()}
+                    class={classes!(format!("{}-enter", prefix), direction)}
+                >
+                    <ContextProvider<TransitionPhase> context={entering}>
+                        { current.children.clone() }
+                    </ContextProvider<TransitionPhase>>
+                </div>
+            </>
+        },
+        None => html! {
+            <>
+                <div
+                    key={current.ctr.to_string()}
+                    class={classes!(format!("{}-current", prefix), direction)}
+                >
+                    <ContextProvider<TransitionPhase> context={entering}>
+                        { current.children.clone() }
+                    </ContextProvider<TransitionPhase>>
+                </div>
+            </>
+        },
+    }
+}
@@ -0,0 +1,342 @@
+//! A router devtools overlay, enabled with the `devtools` feature.
+//!
+//! Render [`RouterDevtools`] anywhere inside a router. It shows the current location and
+//! basename, the routes matched by the mounted [`Switch`](crate::Switch)es with their params and
+//! `sub_path`, the history entries visited since it was mounted, which can be jumped to, and a
+//! log of navigations.
+
+use std::rc::Rc;
+
+use yew::prelude::*;
+
+use crate::events::{NavigationAction, NavigationEvent};
+use crate::history::Location;
+use crate::hooks::{use_location, use_navigation_listener, use_navigator};
+use crate::switch::RouteOutput;
+
+/// The number of navigations kept in the log.
+const LOG_CAPACITY: usize = 100;
+
+const PANEL_STYLE: &str = "position:fixed;right:8px;bottom:8px;z-index:2147483647;\
+                           max-width:480px;max-height:60vh;overflow:auto;padding:8px;\
+                           font:12px monospace;color:#eee;background:rgba(20,20,20,0.92);\
+                           border-radius:4px;";
+
+#[derive(Clone)]
+struct HistoryEntry {
+    id: Option<u32>,
+    path: String,
+}
+
+#[derive(Clone)]
+struct LogEntry {
+    action: NavigationAction,
+    delta: Option<isize>,
+    path: String,
+}
+
+#[derive(Clone, Default)]
+struct DevtoolsState {
+    // The entries of the session history as far as they have been observed.
+    entries: Vec<HistoryEntry>,
+    current: usize,
+    log: Vec<LogEntry>,
+    // The matched routes with their depth in the switch hierarchy.
+    matched: Vec<(usize, RouteOutput)>,
+}
+
+impl Reducible for DevtoolsState {
+    type Action = (NavigationEvent, Vec<(usize, RouteOutput)>);
+
+    fn reduce(self: Rc<Self>, (event, matched): Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+        let entry = HistoryEntry {
+            id: event.location.id(),
+            path: full_path(&event.location),
+        };
+
+        match event.action {
+            NavigationAction::Replace if !state.entries.is_empty() => {
+                state.entries[state.current] = entry;
+            }
+            NavigationAction::Pop if !state.entries.is_empty() => {
+                let index = entry
+                    .id
+                    .and_then(|id| state.entries.iter().position(|it| it.id == Some(id)))
+                    .or_else(|| {
+                        let index = state.current as isize + event.delta?;
+                        usize::try_from(index).ok()
+                    })
+                    .filter(|index| *index < state.entries.len());
+
+                match index {
+                    Some(index) => {
+                        state.current = index;
+                        state.entries[index] = entry;
+                    }
+                    // An entry from before the devtools were mounted.
+                    None => {
+                        state.entries = vec![entry];
+                        state.current = 0;
+                    }
+                }
+            }
+            _ => {
+                state.entries.truncate(state.current + 1);
+                state.entries.push(entry);
+                state.current = state.entries.len() - 1;
+            }
+        }
+
+        if state.log.len() == LOG_CAPACITY {
+            state.log.remove(0);
+        }
+        state.log.push(LogEntry {
+            action: event.action,
+            delta: event.delta,
+            path: full_path(&event.location),
+        });
+        state.matched = matched;
+
+        state.into()
+    }
+}
+
+fn full_path(location: &Location) -> String {
+    format!(
+        "{}{}{}",
+        location.path(),
+        location.query_str(),
+        location.hash()
+    )
+}
+
+/// Props for [`RouterDevtools`].
+#[derive(Properties, PartialEq, Clone)]
+pub struct RouterDevtoolsProps {
+    /// Whether the overlay starts expanded.
+    #[prop_or_default]
+    pub open: bool,
+}
+
+/// An overlay showing the state of the router it is rendered in.
+///
+/// See the [`devtools`](crate::devtools) module.
+#[function_component(RouterDevtools)]
+pub fn router_devtools(props: &RouterDevtoolsProps) -> Html {
+    let open = use_state(|| props.open);
+    let state = use_reducer(DevtoolsState::default);
+    let location = use_location();
+    let navigator = use_navigator();
+
+    {
+        let dispatcher = state.dispatcher();
+        let hub = navigator.as_ref().map(|navigator| navigator.hub().clone());
+        use_navigation_listener(move |event| {
+            let matched = hub
+                .as_ref()
+                .map(|hub| hub.matched_tree())
+                .unwrap_or_default();
+            dispatcher.dispatch((event.clone(), matched));
+        });
+    }
+
+    let toggle = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+
+    if !*open {
+        return html! {
+            <div style={PANEL_STYLE}>
+                <button onclick={toggle}>{ "router" }</button>
+            </div>
+        };
+    }
+
+    let (location, navigator) = match (location, navigator) {
+        (Some(location), Some(navigator)) => (location, navigator),
+        _ => {
+            return html! {
+                <div style={PANEL_STYLE}>{ "RouterDevtools must be rendered inside a router." }</div>
+            }
+        }
+    };
+
+    let matched = state.matched.iter().map(|(depth, output)| {
+        let params = output
+            .params
+            .iter()
+            .map(|(name, value)| format!("{}={:?}", name, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        html! {
+            <li style={format!("padding-left:{}em", depth)}>
+                <code>{ &output.route.path }</code>
+                if !params.is_empty() {
+                    { format!(" params: {}", params) }
+                }
+                if !output.sub_path.is_empty() {
+                    { format!(" sub_path: {:?}", output.sub_path) }
+                }
+            </li>
+        }
+    });
+
+    let entries = state.entries.iter().enumerate().map(|(index, entry)| {
+        let delta = index as isize - state.current as isize;
+        let onclick = {
+            let navigator = navigator.clone();
+            Callback::from(move |_: MouseEvent| navigator.report(navigator.go(delta)))
+        };
+
+        html! {
+            <li>
+                <button {onclick} disabled={delta == 0}>{ format!("{:+}", delta) }</button>
+                { " " }{ &entry.path }
+            </li>
+        }
+    });
+
+    let log = state.log.iter().rev().map(|entry| {
+        let delta = entry
+            .delta
+            .map(|delta| format!(" {:+}", delta))
+            .unwrap_or_default();
+
+        html! {
+            <li>{ format!("{:?}{} {}", entry.action, delta, entry.path) }</li>
+        }
+    });
+
+    html! {
+        <div style={PANEL_STYLE}>
+            <button onclick={toggle}>{ "close" }</button>
+            <h4>{ "Location" }</h4>
+            <ul>
+                <li>{ format!("path: {}", location.path()) }</li>
+                <li>{ format!("query: {}", location.query_str()) }</li>
+                <li>{ format!("hash: {}", location.hash()) }</li>
+                <li>{ format!("basename: {}", navigator.resolved_basename().unwrap_or_default()) }</li>
+                <li>{ format!("history: {:?}", navigator.kind()) }</li>
+            </ul>
+            <h4>{ "Matched routes" }</h4>
+            <ul>{ for matched }</ul>
+            <h4>{ "History" }</h4>
+            <ul>{ for entries }</ul>
+            <h4>{ "Navigations" }</h4>
+            <ul>{ for log }</ul>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{History, MemoryHistory};
+
+    use super::*;
+
+    fn event(
+        history: &MemoryHistory,
+        action: NavigationAction,
+        delta: Option<isize>,
+    ) -> NavigationEvent {
+        NavigationEvent {
+            previous: None,
+            location: history.location(),
+            action,
+            delta,
+            matched: Vec::new(),
+        }
+    }
+
+    fn reduce(state: Rc<DevtoolsState>, event: NavigationEvent) -> Rc<DevtoolsState> {
+        state.reduce((event, Vec::new()))
+    }
+
+    fn paths(state: &DevtoolsState) -> Vec<&str> {
+        state.entries.iter().map(|it| it.path.as_str()).collect()
+    }
+
+    #[test]
+    fn push_drops_the_forward_entries() {
+        let history = MemoryHistory::new();
+        let mut state = Rc::new(DevtoolsState::default());
+        for path in ["/a", "/b", "/c"] {
+            history.push(path);
+            state = reduce(state, event(&history, NavigationAction::Push, None));
+        }
+
+        history.go(-2);
+        state = reduce(state, event(&history, NavigationAction::Pop, Some(-2)));
+        assert_eq!(state.current, 0);
+
+        history.push("/d");
+        state = reduce(state, event(&history, NavigationAction::Push, None));
+        assert_eq!(paths(&state), ["/a", "/d"]);
+        assert_eq!(state.current, 1);
+    }
+
+    #[test]
+    fn replace_overwrites_the_current_entry() {
+        let history = MemoryHistory::new();
+        let mut state = Rc::new(DevtoolsState::default());
+        history.push("/a");
+        state = reduce(state, event(&history, NavigationAction::Push, None));
+        history.push("/b");
+        state = reduce(state, event(&history, NavigationAction::Push, None));
+
+        history.replace("/c");
+        state = reduce(state, event(&history, NavigationAction::Replace, None));
+        assert_eq!(paths(&state), ["/a", "/c"]);
+        assert_eq!(state.current, 1);
+    }
+
+    #[test]
+    fn pop_finds_the_entry_by_its_id() {
+        let history = MemoryHistory::new();
+        let mut state = Rc::new(DevtoolsState::default());
+        for path in ["/a", "/b", "/c"] {
+            history.push(path);
+            state = reduce(state, event(&history, NavigationAction::Push, None));
+        }
+
+        // The back button reports no delta.
+        history.go(-1);
+        state = reduce(state, event(&history, NavigationAction::Pop, None));
+        assert_eq!(paths(&state), ["/a", "/b", "/c"]);
+        assert_eq!(state.current, 1);
+    }
+
+    #[test]
+    fn pop_to_an_unknown_entry_starts_over() {
+        let history = MemoryHistory::new();
+        history.push("/a");
+        let mut state = Rc::new(DevtoolsState::default());
+        history.push("/b");
+        state = reduce(state, event(&history, NavigationAction::Push, None));
+
+        history.go(-1);
+        state = reduce(state, event(&history, NavigationAction::Pop, None));
+        assert_eq!(paths(&state), ["/a"]);
+        assert_eq!(state.current, 0);
+    }
+
+    #[test]
+    fn log_keeps_the_latest_navigations() {
+        let history = MemoryHistory::new();
+        let mut state = Rc::new(DevtoolsState::default());
+        for index in 0..=LOG_CAPACITY {
+            history.push(format!("/{}", index));
+            state = reduce(state, event(&history, NavigationAction::Push, None));
+        }
+
+        assert_eq!(state.log.len(), LOG_CAPACITY);
+        assert_eq!(state.log[0].path, "/1");
+        assert_eq!(
+            state.log.last().map(|it| it.path.as_str()),
+            Some(format!("/{}", LOG_CAPACITY).as_str())
+        );
+    }
+}
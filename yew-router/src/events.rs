@@ -0,0 +1,764 @@
+//! Navigation events delivered after each committed navigation.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll, Waker};
+
+use crate::head::RouteMeta;
+use crate::history::Location;
+use crate::navigator::{NavigationError, NavigationResult};
+use crate::switch::RouteOutput;
+
+/// How the current location was reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationAction {
+    /// A new history entry was pushed.
+    Push,
+    /// The current history entry was replaced.
+    Replace,
+    /// The session history was traversed, e.g. with the back button.
+    ///
+    /// The location the router starts with is reported as a pop as well.
+    Pop,
+}
+
+/// The navigation the current location was reached with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastNavigation {
+    pub action: NavigationAction,
+    /// The `delta` passed to [`Navigator::go`](crate::navigator::Navigator::go) for a pop
+    /// started by a navigator.
+    ///
+    /// Browsers do not report how far the back and forward buttons went, so it is `None` for
+    /// them.
+    pub delta: Option<isize>,
+    /// Whether the navigation is a redirect of the previous one, made by a
+    /// [`Switch`](crate::Switch), a [`Redirect`](crate::components::Redirect) or a
+    /// [`Protected`](crate::components::Protected).
+    pub redirect: bool,
+}
+
+impl LastNavigation {
+    pub(crate) fn new(action: NavigationAction) -> Self {
+        Self {
+            action,
+            delta: None,
+            redirect: false,
+        }
+    }
+}
+
+/// A navigation that has been committed and rendered.
+#[derive(Debug, Clone)]
+pub struct NavigationEvent {
+    /// The location before the navigation, `None` for the first event.
+    pub previous: Option<Location>,
+    pub location: Location,
+    pub action: NavigationAction,
+    /// See [`LastNavigation::delta`].
+    pub delta: Option<isize>,
+    /// Routes matched by the mounted [`Switch`](crate::Switch)es, outermost first.
+    pub matched: Vec<RouteOutput>,
+}
+
+/// A navigation a navigator is about to make, handed to blockers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavigationAttempt {
+    pub action: NavigationAction,
+    /// The location given to the history, `None` for
+    /// [`Navigator::go`](crate::navigator::Navigator::go).
+    pub to: Option<String>,
+    /// The `delta` passed to [`Navigator::go`](crate::navigator::Navigator::go).
+    pub delta: Option<isize>,
+}
+
+type ListenerFn = dyn Fn(&NavigationEvent);
+type BlockerFn = dyn Fn(&NavigationAttempt) -> bool;
+
+/// A handle of a listener registered with
+/// [`Navigator::listen`](crate::navigator::Navigator::listen).
+///
+/// The listener is removed when the handle is dropped.
+#[must_use = "the listener is removed when the handle is dropped"]
+pub struct NavigationListener {
+    _listener: Rc<ListenerFn>,
+}
+
+impl fmt::Debug for NavigationListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NavigationListener").finish()
+    }
+}
+
+/// A handle of a blocker registered with
+/// [`Navigator::block`](crate::navigator::Navigator::block).
+///
+/// The blocker is removed when the handle is dropped.
+#[must_use = "the blocker is removed when the handle is dropped"]
+pub struct NavigationBlocker {
+    _blocker: Rc<BlockerFn>,
+}
+
+impl fmt::Debug for NavigationBlocker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NavigationBlocker").finish()
+    }
+}
+
+/// Holds back the waiters of a navigation while it is alive, see [`NavigationHub::hold`].
+pub(crate) struct NavigationHold {
+    hub: NavigationHub,
+    generation: u64,
+}
+
+impl Drop for NavigationHold {
+    fn drop(&mut self) {
+        self.hub.release(self.generation);
+    }
+}
+
+#[derive(Default)]
+struct WaitState {
+    result: Option<NavigationResult<NavigationEvent>>,
+    waker: Option<Waker>,
+}
+
+impl WaitState {
+    fn resolve(&mut self, result: NavigationResult<NavigationEvent>) {
+        self.result = Some(result);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A navigation started with one of the `_async` methods of
+/// [`Navigator`](crate::navigator::Navigator).
+///
+/// It resolves with the [`NavigationEvent`] once the router has rendered the new location, or
+/// with [`NavigationError::Superseded`] if another navigation happens first.
+///
+/// Redirects of the new route, by a [`Switch`](crate::Switch), a
+/// [`Redirect`](crate::components::Redirect) or a [`Protected`](crate::components::Protected),
+/// continue the navigation, so the future resolves with the location they lead to. A
+/// [`Protected`](crate::components::Protected) holds it back until `authorize` resolves.
+#[must_use = "futures do nothing unless polled"]
+pub struct NavigationFuture {
+    state: Rc<RefCell<WaitState>>,
+}
+
+impl NavigationFuture {
+    pub(crate) fn ready(result: NavigationResult<NavigationEvent>) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(WaitState {
+                result: Some(result),
+                waker: None,
+            })),
+        }
+    }
+}
+
+impl fmt::Debug for NavigationFuture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NavigationFuture").finish()
+    }
+}
+
+impl Future for NavigationFuture {
+    type Output = NavigationResult<NavigationEvent>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.borrow_mut();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct HubState {
+    pending: Option<LastNavigation>,
+    previous: Option<Location>,
+    listeners: Vec<Weak<ListenerFn>>,
+    blockers: Vec<Weak<BlockerFn>>,
+    // Every navigation gets a generation, awaited ones resolve when theirs is emitted.
+    generation: u64,
+    emitted: Option<(u64, NavigationEvent)>,
+    waiters: Vec<(u64, Rc<RefCell<WaitState>>)>,
+    // The location a redirect of the current navigation leads to, until it is rendered.
+    continuation: Option<String>,
+    // The number of components that must settle before the navigation of a generation is.
+    holds: BTreeMap<u64, usize>,
+    next_switch_id: u32,
+    // The switch each switch is rendered in.
+    parents: BTreeMap<u32, Option<u32>>,
+    matches: BTreeMap<u32, RouteOutput>,
+    metas: BTreeMap<u32, RouteMeta>,
+}
+
+impl HubState {
+    /// Returns whether the current navigation is neither redirected nor held back.
+    fn is_settled(&self) -> bool {
+        self.continuation.is_none() && !self.holds.contains_key(&self.generation)
+    }
+}
+
+/// State shared between a router, its navigators and switches.
+#[derive(Clone, Default)]
+pub(crate) struct NavigationHub {
+    inner: Rc<RefCell<HubState>>,
+}
+
+impl PartialEq for NavigationHub {
+    fn eq(&self, rhs: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &rhs.inner)
+    }
+}
+
+impl fmt::Debug for NavigationHub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NavigationHub").finish()
+    }
+}
+
+impl NavigationHub {
+    /// Records a navigation started by a navigator.
+    pub fn set_pending(&self, navigation: LastNavigation) {
+        self.inner.borrow_mut().pending = Some(navigation);
+    }
+
+    pub fn clear_pending(&self) {
+        self.inner.borrow_mut().pending = None;
+    }
+
+    /// Takes the recorded navigation. Changes not started by a navigator are pops.
+    pub fn take_pending(&self) -> LastNavigation {
+        let pending = self.inner.borrow_mut().pending.take();
+        pending.unwrap_or_else(|| {
+            self.begin();
+            LastNavigation::new(NavigationAction::Pop)
+        })
+    }
+
+    /// Starts a new generation, superseding the navigations awaited so far.
+    pub fn begin(&self) -> u64 {
+        let (generation, superseded) = {
+            let mut state = self.inner.borrow_mut();
+            state.generation += 1;
+            state.continuation = None;
+            (state.generation, std::mem::take(&mut state.waiters))
+        };
+
+        for (_, waiter) in superseded {
+            waiter
+                .borrow_mut()
+                .resolve(Err(NavigationError::Superseded));
+        }
+        generation
+    }
+
+    /// Continues the current navigation with a redirect to `url`, the location the history is
+    /// given.
+    ///
+    /// Its waiters resolve once `url` is rendered instead of the location redirected away from.
+    pub fn continue_with(&self, url: &str) {
+        self.inner.borrow_mut().continuation = Some(url.to_string());
+    }
+
+    /// Returns whether the current navigation has not been rendered yet or is being redirected.
+    pub fn is_navigating(&self) -> bool {
+        let state = self.inner.borrow();
+        let rendered = matches!(
+            &state.emitted,
+            Some((generation, _)) if *generation == state.generation
+        );
+        !rendered || state.continuation.is_some()
+    }
+
+    /// Holds back the waiters of the current navigation until the returned guard is dropped.
+    ///
+    /// Later navigations are not held back.
+    pub fn hold(&self) -> NavigationHold {
+        let mut state = self.inner.borrow_mut();
+        let generation = state.generation;
+        *state.holds.entry(generation).or_default() += 1;
+
+        NavigationHold {
+            hub: self.clone(),
+            generation,
+        }
+    }
+
+    fn release(&self, generation: u64) {
+        let settled = {
+            let mut state = self.inner.borrow_mut();
+            if let Some(holds) = state.holds.get_mut(&generation) {
+                *holds -= 1;
+                if *holds == 0 {
+                    state.holds.remove(&generation);
+                }
+            }
+
+            match &state.emitted {
+                Some((emitted, event)) if *emitted == generation && state.is_settled() => {
+                    Some(event.clone())
+                }
+                _ => None,
+            }
+        };
+
+        if let Some(event) = settled {
+            self.resolve(generation, || Ok(event.clone()));
+        }
+    }
+
+    fn resolve(&self, generation: u64, result: impl Fn() -> NavigationResult<NavigationEvent>) {
+        let done: Vec<_> = {
+            let mut state = self.inner.borrow_mut();
+            let (done, waiting) = std::mem::take(&mut state.waiters)
+                .into_iter()
+                .partition(|(it, _)| *it == generation);
+            state.waiters = waiting;
+            done
+        };
+
+        for (_, waiter) in done {
+            waiter.borrow_mut().resolve(result());
+        }
+    }
+
+    /// Returns a future for the current generation.
+    pub fn wait(&self) -> NavigationFuture {
+        let mut state = self.inner.borrow_mut();
+        let generation = state.generation;
+
+        // The navigation may have been rendered synchronously, e.g. a fragment change.
+        let result = match &state.emitted {
+            Some((emitted, event)) if *emitted == generation && state.is_settled() => {
+                Some(Ok(event.clone()))
+            }
+            _ => None,
+        };
+        let future = NavigationFuture {
+            state: Rc::new(RefCell::new(WaitState {
+                result,
+                waker: None,
+            })),
+        };
+        if future.state.borrow().result.is_none() {
+            state.waiters.push((generation, future.state.clone()));
+        }
+        future
+    }
+
+    pub fn listen<CB>(&self, callback: CB) -> NavigationListener
+    where
+        CB: Fn(&NavigationEvent) + 'static,
+    {
+        let listener = Rc::new(callback) as Rc<ListenerFn>;
+        self.inner
+            .borrow_mut()
+            .listeners
+            .push(Rc::downgrade(&listener));
+
+        NavigationListener {
+            _listener: listener,
+        }
+    }
+
+    pub fn block<CB>(&self, callback: CB) -> NavigationBlocker
+    where
+        CB: Fn(&NavigationAttempt) -> bool + 'static,
+    {
+        let blocker = Rc::new(callback) as Rc<BlockerFn>;
+        self.inner
+            .borrow_mut()
+            .blockers
+            .push(Rc::downgrade(&blocker));
+
+        NavigationBlocker { _blocker: blocker }
+    }
+
+    /// Returns whether a registered blocker refuses `attempt`.
+    pub fn is_blocked(&self, attempt: &NavigationAttempt) -> bool {
+        let blockers: Vec<_> = {
+            let mut state = self.inner.borrow_mut();
+            state.blockers.retain(|it| it.strong_count() > 0);
+            state.blockers.iter().filter_map(Weak::upgrade).collect()
+        };
+
+        blockers.iter().any(|blocker| blocker(attempt))
+    }
+
+    /// Registers a switch rendered in the switch `parent`, returning its id.
+    pub fn register_switch(&self, parent: Option<u32>) -> u32 {
+        let mut state = self.inner.borrow_mut();
+        state.next_switch_id += 1;
+        let id = state.next_switch_id;
+        state.parents.insert(id, parent);
+        id
+    }
+
+    pub fn unregister_switch(&self, id: u32) {
+        let mut state = self.inner.borrow_mut();
+        state.parents.remove(&id);
+        state.matches.remove(&id);
+        state.metas.remove(&id);
+    }
+
+    pub fn set_match(&self, id: u32, output: Option<RouteOutput>) {
+        let mut state = self.inner.borrow_mut();
+        match output {
+            Some(output) => state.matches.insert(id, output),
+            None => state.matches.remove(&id),
+        };
+    }
+
+    pub fn set_meta(&self, id: u32, meta: Option<RouteMeta>) {
+        let mut state = self.inner.borrow_mut();
+        match meta {
+            Some(meta) => state.metas.insert(id, meta),
+            None => state.metas.remove(&id),
+        };
+    }
+
+    /// Returns the matched routes in the order of the switch hierarchy, each with its depth among
+    /// the switches with a match.
+    #[cfg(feature = "devtools")]
+    pub fn matched_tree(&self) -> Vec<(usize, RouteOutput)> {
+        let state = self.inner.borrow();
+        // The closest switch with a match the switch `id` is rendered in.
+        let parent_of = |id: u32| {
+            let mut parent = state.parents.get(&id).copied().flatten();
+            while let Some(id) = parent {
+                if state.matches.contains_key(&id) {
+                    return Some(id);
+                }
+                parent = state.parents.get(&id).copied().flatten();
+            }
+            None
+        };
+        let children_of = |parent: Option<u32>| {
+            state
+                .matches
+                .keys()
+                .copied()
+                .filter(|id| parent_of(*id) == parent)
+                .collect::<Vec<_>>()
+        };
+
+        let mut tree = Vec::new();
+        let mut stack: Vec<_> = children_of(None)
+            .into_iter()
+            .rev()
+            .map(|id| (0, id))
+            .collect();
+        while let Some((depth, id)) = stack.pop() {
+            tree.push((depth, state.matches[&id].clone()));
+            stack.extend(
+                children_of(Some(id))
+                    .into_iter()
+                    .rev()
+                    .map(|child| (depth + 1, child)),
+            );
+        }
+        tree
+    }
+
+    /// Returns the metadata of all matched routes merged, `None` if no route has any.
+    pub fn meta(&self) -> Option<RouteMeta> {
+        self.inner
+            .borrow()
+            .metas
+            .values()
+            .cloned()
+            .reduce(RouteMeta::merge)
+    }
+
+    /// Notifies listeners of a committed navigation.
+    pub fn emit(&self, location: Location, navigation: LastNavigation) {
+        let _span = tracing::debug_span!(
+            "navigation",
+            action = ?navigation.action,
+            delta = ?navigation.delta,
+            path = %location.path(),
+        )
+        .entered();
+        let (event, listeners, settled) = {
+            let mut state = self.inner.borrow_mut();
+            state.listeners.retain(|it| it.strong_count() > 0);
+
+            let event = NavigationEvent {
+                previous: state.previous.replace(location.clone()),
+                location,
+                action: navigation.action,
+                delta: navigation.delta,
+                // Switches are registered in mount order, so parents come before children.
+                matched: state.matches.values().cloned().collect(),
+            };
+            let listeners: Vec<_> = state.listeners.iter().filter_map(Weak::upgrade).collect();
+
+            let generation = state.generation;
+            state.emitted = Some((generation, event.clone()));
+
+            // A location redirected away from does not settle the navigation.
+            let url = format!(
+                "{}{}{}",
+                event.location.path(),
+                event.location.query_str(),
+                event.location.hash()
+            );
+            if state.continuation.as_ref() == Some(&url) {
+                state.continuation = None;
+            }
+            let settled = state.is_settled().then_some(generation);
+
+            (event, listeners, settled)
+        };
+
+        tracing::debug!(matched = event.matched.len(), "navigation rendered");
+        if let Some(generation) = settled {
+            self.resolve(generation, || Ok(event.clone()));
+        }
+
+        // The state is released first as listeners are free to navigate.
+        for listener in listeners {
+            listener(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{History, MemoryHistory};
+
+    use super::*;
+
+    fn location(path: &str) -> Location {
+        let history = MemoryHistory::new();
+        history.push(path);
+        history.location()
+    }
+
+    fn result(future: &NavigationFuture) -> Option<NavigationResult<NavigationEvent>> {
+        future.state.borrow_mut().result.take()
+    }
+
+    #[test]
+    fn listeners_are_called_in_registration_order() {
+        let hub = NavigationHub::default();
+        let calls = Rc::new(RefCell::new(Vec::new()));
+
+        let first = {
+            let calls = calls.clone();
+            hub.listen(move |event| {
+                calls
+                    .borrow_mut()
+                    .push(("first", event.location.path().to_string()))
+            })
+        };
+        let second = {
+            let calls = calls.clone();
+            hub.listen(move |event| {
+                calls
+                    .borrow_mut()
+                    .push(("second", event.location.path().to_string()))
+            })
+        };
+
+        hub.emit(location("/a"), LastNavigation::new(NavigationAction::Push));
+        drop(first);
+        hub.emit(location("/b"), LastNavigation::new(NavigationAction::Push));
+        drop(second);
+
+        assert_eq!(
+            *calls.borrow(),
+            [
+                ("first", "/a".to_string()),
+                ("second", "/a".to_string()),
+                ("second", "/b".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn events_carry_the_previous_location() {
+        let hub = NavigationHub::default();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let _listener = {
+            let events = events.clone();
+            hub.listen(move |event| events.borrow_mut().push(event.clone()))
+        };
+
+        hub.emit(location("/a"), LastNavigation::new(NavigationAction::Pop));
+        hub.emit(
+            location("/b"),
+            LastNavigation::new(NavigationAction::Replace),
+        );
+
+        let events = events.borrow();
+        assert!(events[0].previous.is_none());
+        assert_eq!(events[1].previous.as_ref().map(|it| it.path()), Some("/a"));
+        assert_eq!(events[1].action, NavigationAction::Replace);
+    }
+
+    #[test]
+    fn wait_resolves_when_its_generation_is_emitted() {
+        let hub = NavigationHub::default();
+
+        let generation = hub.begin();
+        let future = hub.wait();
+        assert!(result(&future).is_none());
+
+        hub.emit(location("/a"), LastNavigation::new(NavigationAction::Push));
+        let event = result(&future).unwrap().unwrap();
+        assert_eq!(event.location.path(), "/a");
+        assert_eq!(hub.begin(), generation + 1);
+    }
+
+    #[test]
+    fn wait_after_emission_is_ready() {
+        let hub = NavigationHub::default();
+
+        hub.begin();
+        hub.emit(location("/a"), LastNavigation::new(NavigationAction::Push));
+
+        let event = result(&hub.wait()).unwrap().unwrap();
+        assert_eq!(event.location.path(), "/a");
+    }
+
+    #[test]
+    fn begin_supersedes_waiters() {
+        let hub = NavigationHub::default();
+
+        hub.begin();
+        let first = hub.wait();
+        hub.begin();
+        let second = hub.wait();
+
+        assert!(matches!(
+            result(&first),
+            Some(Err(NavigationError::Superseded))
+        ));
+        assert!(result(&second).is_none());
+
+        hub.emit(location("/b"), LastNavigation::new(NavigationAction::Push));
+        assert!(matches!(result(&second), Some(Ok(_))));
+    }
+
+    #[test]
+    fn continue_with_settles_on_the_redirect_target() {
+        let hub = NavigationHub::default();
+
+        hub.begin();
+        let future = hub.wait();
+        assert!(hub.is_navigating());
+
+        hub.continue_with("/login");
+        hub.emit(location("/a"), LastNavigation::new(NavigationAction::Push));
+        assert!(result(&future).is_none());
+        assert!(hub.is_navigating());
+
+        hub.emit(
+            location("/login"),
+            LastNavigation::new(NavigationAction::Replace),
+        );
+        let event = result(&future).unwrap().unwrap();
+        assert_eq!(event.location.path(), "/login");
+        assert!(!hub.is_navigating());
+    }
+
+    #[test]
+    fn hold_delays_its_navigation() {
+        let hub = NavigationHub::default();
+
+        hub.begin();
+        let future = hub.wait();
+        let hold = hub.hold();
+
+        hub.emit(location("/a"), LastNavigation::new(NavigationAction::Push));
+        assert!(result(&future).is_none());
+
+        drop(hold);
+        let event = result(&future).unwrap().unwrap();
+        assert_eq!(event.location.path(), "/a");
+    }
+
+    #[test]
+    fn stale_holds_do_not_delay_later_navigations() {
+        let hub = NavigationHub::default();
+
+        hub.begin();
+        let hold = hub.hold();
+        hub.emit(location("/a"), LastNavigation::new(NavigationAction::Push));
+
+        hub.begin();
+        let future = hub.wait();
+        hub.emit(location("/b"), LastNavigation::new(NavigationAction::Push));
+        let event = result(&future).unwrap().unwrap();
+        assert_eq!(event.location.path(), "/b");
+
+        drop(hold);
+        assert!(result(&hub.wait()).unwrap().is_ok());
+    }
+
+    #[cfg(feature = "devtools")]
+    fn output(path: &str) -> RouteOutput {
+        RouteOutput {
+            sub_path: String::new(),
+            route: nested_router::Route {
+                path: path.to_string(),
+                has_sub_routes: false,
+            },
+            params: BTreeMap::new(),
+        }
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn matched_tree_follows_the_switch_hierarchy() {
+        let hub = NavigationHub::default();
+        let root = hub.register_switch(None);
+        let settings = hub.register_switch(Some(root));
+        let sidebar = hub.register_switch(None);
+        // A switch without a match is skipped, its children hang off the closest match.
+        let empty = hub.register_switch(Some(settings));
+        let profile = hub.register_switch(Some(empty));
+
+        hub.set_match(root, Some(output("settings/*")));
+        hub.set_match(settings, Some(output("account/*")));
+        hub.set_match(sidebar, Some(output("*")));
+        hub.set_match(profile, Some(output("profile")));
+
+        let tree: Vec<_> = hub
+            .matched_tree()
+            .into_iter()
+            .map(|(depth, output)| (depth, output.route.path))
+            .collect();
+        assert_eq!(
+            tree,
+            [
+                (0, "settings/*".to_string()),
+                (1, "account/*".to_string()),
+                (2, "profile".to_string()),
+                (0, "*".to_string()),
+            ]
+        );
+
+        hub.unregister_switch(profile);
+        let depths: Vec<_> = hub
+            .matched_tree()
+            .into_iter()
+            .map(|(depth, _)| depth)
+            .collect();
+        assert_eq!(depths, [0, 1, 0]);
+    }
+}
@@ -0,0 +1,58 @@
+//! Fragment (`#anchor`) handling.
+//!
+//! A navigation that only changes the fragment of the current page scrolls to the anchor
+//! without re-rendering the routes, moving the focus or announcing a new page, and a navigation
+//! to another page scrolls to its anchor once the page is rendered. Going back or forward does not scroll, so the browser
+//! restores the previous position. [`use_fragment`](crate::hooks::use_fragment) reports the current
+//! fragment.
+
+use crate::history::Location;
+use crate::utils::decode_segment;
+
+/// Fragment accessors for [`Location`].
+pub trait LocationExt {
+    /// Returns the decoded fragment without the leading `#`, `None` if there is none.
+    fn fragment(&self) -> Option<String>;
+
+    /// Returns whether `other` is the same page, i.e. only the fragment may differ.
+    fn same_page(&self, other: &Location) -> bool;
+}
+
+impl LocationExt for Location {
+    fn fragment(&self) -> Option<String> {
+        let hash = self.hash();
+        let fragment = hash.strip_prefix('#').unwrap_or(hash);
+
+        if fragment.is_empty() {
+            None
+        } else {
+            Some(decode_segment(fragment).into_owned())
+        }
+    }
+
+    fn same_page(&self, other: &Location) -> bool {
+        self.path() == other.path() && self.query_str() == other.query_str()
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct FragmentContext {
+    pub fragment: Option<String>,
+}
+
+/// Scrolls the element whose id is `fragment` into view.
+///
+/// Without such an element, `top` (in any case) scrolls to the top of the page like browsers do,
+/// and any other fragment leaves the scroll position alone. An empty fragment is never passed,
+/// as [`LocationExt::fragment`] returns `None` for it.
+pub(crate) fn scroll_to_fragment(fragment: &str) {
+    let document = gloo::utils::document();
+
+    match document.get_element_by_id(fragment) {
+        Some(element) => element.scroll_into_view(),
+        None if fragment.eq_ignore_ascii_case("top") => {
+            gloo::utils::window().scroll_to_with_x_and_y(0.0, 0.0)
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,254 @@
+//! Per-route document metadata.
+//!
+//! A [`Switch`](crate::Switch) attaches [`RouteMeta`] to its routes with the `meta` prop. After
+//! each committed navigation the router merges the metadata of all matched routes, inner ones
+//! taking precedence, and applies it to the document. [`route_meta`] and [`render_head`] do the
+//! same without a DOM so that a server can inject the result into `<head>`.
+
+use std::collections::BTreeMap;
+
+use nested_router::RouteList;
+use web_sys::Document;
+
+/// Metadata of a route.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RouteMeta {
+    /// The page title, inserted into the `title_template` of the router if there is one.
+    pub title: Option<String>,
+    /// Content of `<meta name="description">`.
+    pub description: Option<String>,
+    /// `href` of `<link rel="canonical">`.
+    pub canonical: Option<String>,
+    /// Text a router with `announce` set announces instead of the title.
+    pub announcement: Option<String>,
+    /// Selector of the element focused after navigating to the route, overriding the `focus`
+    /// of the router.
+    pub focus: Option<String>,
+}
+
+impl RouteMeta {
+    /// Returns `self` with the fields `inner` sets overridden.
+    pub fn merge(self, inner: RouteMeta) -> Self {
+        Self {
+            title: inner.title.or(self.title),
+            description: inner.description.or(self.description),
+            canonical: inner.canonical.or(self.canonical),
+            announcement: inner.announcement.or(self.announcement),
+            focus: inner.focus.or(self.focus),
+        }
+    }
+
+    /// Returns the title with `{}` in `title_template` replaced by it.
+    pub fn full_title(&self, title_template: Option<&str>) -> Option<String> {
+        let title = self.title.as_deref()?;
+
+        Some(match title_template {
+            Some(template) => template.replacen("{}", title, 1),
+            None => title.to_string(),
+        })
+    }
+}
+
+/// Returns the metadata `meta` attaches to the route of `routes` matching `pathname`.
+///
+/// `meta` is keyed by [`Route::path`](nested_router::Route::path), like the `meta` prop of
+/// [`Switch`](crate::Switch).
+///
+/// Only `routes` itself is matched. For nested switches, call this again with the list of the
+/// nested switch and the sub path left by the outer route, then [`merge`](RouteMeta::merge) the
+/// results from the outermost to the innermost one, as the router does.
+pub fn route_meta(
+    routes: &RouteList,
+    meta: &BTreeMap<String, RouteMeta>,
+    pathname: &str,
+) -> Option<RouteMeta> {
+    let output = routes.route(pathname).ok()?;
+    meta.get(&output.route.path).cloned()
+}
+
+/// Renders the tags for `meta` to be placed inside `<head>`.
+pub fn render_head(meta: &RouteMeta, title_template: Option<&str>) -> String {
+    let mut head = String::new();
+
+    if let Some(title) = meta.full_title(title_template) {
+        head.push_str(&format!("<title>{}</title>", escape(&title)));
+    }
+    if let Some(description) = &meta.description {
+        head.push_str(&format!(
+            r#"<meta name="description" content="{}">"#,
+            escape(description)
+        ));
+    }
+    if let Some(canonical) = &meta.canonical {
+        head.push_str(&format!(
+            r#"<link rel="canonical" href="{}">"#,
+            escape(canonical)
+        ));
+    }
+
+    head
+}
+
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Applies `meta` to the current document.
+///
+/// Fields `meta` leaves unset are reset: the title to `base_title` and the description and
+/// canonical link are removed, so nothing of the previous route is left behind.
+pub(crate) fn apply_head(meta: &RouteMeta, title_template: Option<&str>, base_title: &str) {
+    let document = gloo::utils::document();
+
+    match meta.full_title(title_template) {
+        Some(title) => document.set_title(&title),
+        None => document.set_title(base_title),
+    }
+    match &meta.description {
+        Some(description) => set_head_element(
+            &document,
+            r#"meta[name="description"]"#,
+            "meta",
+            &[("name", "description"), ("content", description)],
+        ),
+        None => remove_head_element(&document, r#"meta[name="description"]"#),
+    }
+    match &meta.canonical {
+        Some(canonical) => set_head_element(
+            &document,
+            r#"link[rel="canonical"]"#,
+            "link",
+            &[("rel", "canonical"), ("href", canonical)],
+        ),
+        None => remove_head_element(&document, r#"link[rel="canonical"]"#),
+    }
+}
+
+fn set_head_element(document: &Document, selector: &str, tag: &str, attributes: &[(&str, &str)]) {
+    let element = match document.query_selector(selector) {
+        Ok(Some(element)) => element,
+        _ => {
+            let element = match document.create_element(tag) {
+                Ok(element) => element,
+                Err(_) => return,
+            };
+            if let Some(head) = document.head() {
+                let _ = head.append_child(&element);
+            }
+            element
+        }
+    };
+
+    for (name, value) in attributes {
+        let _ = element.set_attribute(name, value);
+    }
+}
+
+fn remove_head_element(document: &Document, selector: &str) {
+    if let Ok(Some(element)) = document.query_selector(selector) {
+        element.remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nested_router::Route;
+
+    use super::*;
+
+    fn meta(title: &str) -> RouteMeta {
+        RouteMeta {
+            title: Some(title.to_string()),
+            ..RouteMeta::default()
+        }
+    }
+
+    #[test]
+    fn full_title_fills_the_template() {
+        let meta = meta("Settings");
+        assert_eq!(
+            meta.full_title(Some("{} | App")),
+            Some("Settings | App".to_string())
+        );
+        assert_eq!(
+            meta.full_title(Some("{} | {}")),
+            Some("Settings | {}".to_string())
+        );
+        assert_eq!(meta.full_title(Some("App")), Some("App".to_string()));
+        assert_eq!(meta.full_title(None), Some("Settings".to_string()));
+        assert_eq!(RouteMeta::default().full_title(Some("{} | App")), None);
+    }
+
+    #[test]
+    fn merge_prefers_the_inner_route() {
+        let outer = RouteMeta {
+            title: Some("Settings".to_string()),
+            description: Some("All settings".to_string()),
+            focus: Some("main".to_string()),
+            ..RouteMeta::default()
+        };
+        let inner = RouteMeta {
+            title: Some("Profile".to_string()),
+            canonical: Some("/settings/profile".to_string()),
+            ..RouteMeta::default()
+        };
+
+        assert_eq!(
+            outer.merge(inner),
+            RouteMeta {
+                title: Some("Profile".to_string()),
+                description: Some("All settings".to_string()),
+                canonical: Some("/settings/profile".to_string()),
+                announcement: None,
+                focus: Some("main".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn render_head_escapes_values() {
+        let meta = RouteMeta {
+            title: Some("<Tom & Jerry>".to_string()),
+            description: Some(r#"say "hi" & 'bye'"#.to_string()),
+            canonical: Some("/a?b=1&c='2'".to_string()),
+            ..RouteMeta::default()
+        };
+
+        assert_eq!(
+            render_head(&meta, Some("{} | App")),
+            concat!(
+                "<title>&lt;Tom &amp; Jerry&gt; | App</title>",
+                r#"<meta name="description" content="say &quot;hi&quot; &amp; &#39;bye&#39;">"#,
+                r#"<link rel="canonical" href="/a?b=1&amp;c=&#39;2&#39;">"#,
+            )
+        );
+        assert_eq!(render_head(&RouteMeta::default(), Some("{} | App")), "");
+    }
+
+    #[test]
+    fn route_meta_matches_the_top_level_route() {
+        let routes = RouteList {
+            routes: vec![
+                Route {
+                    path: "".to_string(),
+                    has_sub_routes: false,
+                },
+                Route {
+                    path: "settings".to_string(),
+                    has_sub_routes: true,
+                },
+            ],
+        };
+        let meta = BTreeMap::from([("settings".to_string(), self::meta("Settings"))]);
+
+        assert_eq!(
+            route_meta(&routes, &meta, "settings"),
+            Some(self::meta("Settings"))
+        );
+        assert_eq!(route_meta(&routes, &meta, ""), None);
+    }
+}
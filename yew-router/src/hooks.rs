@@ -1,10 +1,20 @@
 //! Hooks to access router state and navigate between pages.
 
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
 use yew::prelude::*;
 
+use crate::components::return_to;
+use crate::events::{LastNavigation, NavigationAttempt, NavigationEvent};
+use crate::fragment::FragmentContext;
 use crate::history::*;
-use crate::navigator::Navigator;
+use crate::keep_alive::KeepAliveContext;
+use crate::navigator::{NavigationResult, Navigator};
+use crate::outlets::outlet_path;
 use crate::router::{LocationContext, NavigatorContext};
+use crate::state::{self, BackgroundState, LocationStateError};
 
 /// A hook to access the [`Navigator`].
 #[hook]
@@ -17,3 +27,237 @@ pub fn use_navigator() -> Option<Navigator> {
 pub fn use_location() -> Option<Location> {
     Some(use_context::<LocationContext>()?.location())
 }
+
+/// State handle for the [`use_basename`] hook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UseBasenameHandle {
+    basename: Option<String>,
+    params: BTreeMap<String, String>,
+    navigator: Navigator,
+}
+
+impl UseBasenameHandle {
+    /// Returns the basename resolved against the current location.
+    pub fn basename(&self) -> Option<&str> {
+        self.basename.as_deref()
+    }
+
+    /// Returns the values of the `:name` segments of the basename.
+    pub fn params(&self) -> &BTreeMap<String, String> {
+        &self.params
+    }
+
+    /// Returns the value of the `:name` segment `name`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(|value| value.as_str())
+    }
+
+    /// Stays on the current route under the basename with the segment `name` set to `value`.
+    pub fn set_param(&self, name: &str, value: &str) -> NavigationResult<()> {
+        let params = BTreeMap::from([(name.to_string(), value.to_string())]);
+        self.navigator.push_basename(&params)
+    }
+}
+
+/// A hook to access and switch the params of a parameterized basename, e.g. the tenant of
+/// `/t/:tenant`.
+///
+/// See the [`basename`](crate::basename) module.
+#[hook]
+pub fn use_basename() -> Option<UseBasenameHandle> {
+    // Subscribes to location changes.
+    let _location = use_location()?;
+    let navigator = use_navigator()?;
+
+    Some(UseBasenameHandle {
+        basename: navigator.resolved_basename(),
+        params: navigator.basename_params(),
+        navigator,
+    })
+}
+
+/// A hook to access the locale of the current [`Location`], `None` if the router has no
+/// [`Locales`](crate::locale::Locales).
+#[hook]
+pub fn use_locale() -> Option<String> {
+    // Subscribes to location changes.
+    let _location = use_location()?;
+    use_navigator()?.locale()
+}
+
+/// A hook to access the pathname to hand to a top-level [`Switch`](crate::Switch).
+///
+/// The basename is removed, and so are the locale segment and localized slugs if the router has
+/// [`Locales`](crate::locale::Locales), so routes are declared once with their logical slugs.
+/// Like the pathnames of nested switches, the result has no leading slash.
+#[hook]
+pub fn use_route_pathname() -> Option<String> {
+    let location = use_location()?;
+    let navigator = use_navigator()?;
+
+    let path = navigator.strip_basename(location.path());
+    let path = match navigator.locales() {
+        Some(locales) => locales.to_logical(&path).1,
+        None => path.into_owned(),
+    };
+
+    Some(path.trim_start_matches('/').to_string())
+}
+
+/// A hook to access the decoded fragment of the current location, `None` if there is none.
+///
+/// A navigation that only changes the fragment does not re-render the routes, so the
+/// [`Location`] of [`use_location`] may still carry the previous fragment. Components using this
+/// hook are re-rendered for such navigations.
+#[hook]
+pub fn use_fragment() -> Option<String> {
+    use_context::<FragmentContext>()?.fragment
+}
+
+/// A hook to access the route a [`Protected`](crate::components::Protected) redirected away from
+/// to the current login route.
+#[hook]
+pub fn use_return_to() -> Option<String> {
+    let location = use_location()?;
+    return_to(location.query_str())
+}
+
+/// A hook to access the path of the named outlet `name`, `None` if the outlet is closed.
+///
+/// See the [`outlets`](crate::outlets) module.
+#[hook]
+pub fn use_outlet_path(name: &str) -> Option<String> {
+    let location = use_location()?;
+    outlet_path(location.query_str(), name)
+}
+
+/// A hook to access the location a modal route is rendered over.
+///
+/// A location pushed with [`Navigator::push_with_background`] or a [`Link`](crate::components::Link)
+/// with `modal` set carries the location it was opened from. Render that background with one
+/// [`Switch`](crate::Switch) and the current location with another to show the route as a modal.
+/// On a direct load or reload there is no background, so the route renders as a full page.
+#[hook]
+pub fn use_background_location() -> Option<Location> {
+    let location = use_location()?;
+    let background = location.state::<BackgroundState>()?;
+
+    Some(background.0.clone())
+}
+
+/// A hook to read the state of the current [`Location`] stored with
+/// [`Navigator::push_with_serde_state`] or [`Navigator::replace_with_serde_state`].
+///
+/// Returns `None` if there is no such state and an error if it is not a valid `T`.
+#[hook]
+pub fn use_location_state<T>() -> Option<Result<T, LocationStateError>>
+where
+    T: DeserializeOwned + 'static,
+{
+    let location = use_location();
+    let navigator = use_navigator();
+
+    state::read(&location?, navigator?.kind())
+}
+
+/// A hook to access how the current [`Location`] was reached.
+///
+/// This allows direction-aware transitions, e.g. animating a pop differently from a push.
+#[hook]
+pub fn use_last_navigation() -> Option<LastNavigation> {
+    Some(use_context::<LocationContext>()?.navigation())
+}
+
+/// A hook to run a callback after each committed navigation.
+///
+/// The latest callback passed to the hook is the one being called, so it may capture values of
+/// the current render.
+#[hook]
+pub fn use_navigation_listener<F>(f: F)
+where
+    F: Fn(&NavigationEvent) + 'static,
+{
+    let navigator = use_navigator();
+
+    let f = Rc::new(f) as Rc<dyn Fn(&NavigationEvent)>;
+    let current = use_mut_ref(|| f.clone());
+    *current.borrow_mut() = f;
+
+    use_effect_with_deps(
+        move |navigator| {
+            let listener = navigator.as_ref().map(|navigator| {
+                navigator.listen(move |event| {
+                    let f = current.borrow().clone();
+                    f(event)
+                })
+            });
+
+            move || std::mem::drop(listener)
+        },
+        navigator,
+    );
+}
+
+/// A hook to block navigations made by navigators while the component is mounted.
+///
+/// The callback returns `true` to refuse a navigation, see [`Navigator::block`]. The latest
+/// callback passed to the hook is the one being called.
+#[hook]
+pub fn use_navigation_blocker<F>(f: F)
+where
+    F: Fn(&NavigationAttempt) -> bool + 'static,
+{
+    let navigator = use_navigator();
+
+    let f = Rc::new(f) as Rc<dyn Fn(&NavigationAttempt) -> bool>;
+    let current = use_mut_ref(|| f.clone());
+    *current.borrow_mut() = f;
+
+    use_effect_with_deps(
+        move |navigator| {
+            let blocker = navigator.as_ref().map(|navigator| {
+                navigator.block(move |attempt| {
+                    let f = current.borrow().clone();
+                    f(attempt)
+                })
+            });
+
+            move || std::mem::drop(blocker)
+        },
+        navigator,
+    );
+}
+
+/// A hook to tell whether the route rendering the component is the active one.
+///
+/// It is `false` inside a route that a [`Switch`](crate::Switch) with `keep_alive` keeps mounted
+/// while hidden, and `true` everywhere else.
+#[hook]
+pub fn use_route_active() -> bool {
+    use_context::<KeepAliveContext>().map_or(true, |ctx| ctx.active)
+}
+
+/// A hook to run a callback when the route rendering the component is activated (`true`) or
+/// deactivated (`false`) by a [`Switch`](crate::Switch) with `keep_alive`.
+///
+/// It is not called when the component is mounted.
+#[hook]
+pub fn use_route_activation<F>(f: F)
+where
+    F: Fn(bool) + 'static,
+{
+    let active = use_route_active();
+    let previous = use_mut_ref(|| active);
+
+    use_effect_with_deps(
+        move |active| {
+            if *previous.borrow() != *active {
+                *previous.borrow_mut() = *active;
+                f(*active);
+            }
+
+            || {}
+        },
+        active,
+    );
+}
@@ -0,0 +1,148 @@
+//! The cache behind the `keep_alive` prop of [`Switch`](crate::Switch).
+
+use yew::prelude::*;
+
+use crate::switch::RouteOutput;
+
+/// Tells components inside a kept alive route whether the route is the active one.
+#[derive(Clone, PartialEq)]
+pub(crate) struct KeepAliveContext {
+    pub active: bool,
+}
+
+struct CacheEntry {
+    key: String,
+    html: Html,
+    last_used: u64,
+}
+
+/// The rendered routes of a switch, least recently used ones are evicted first.
+///
+/// Entries stay in insertion order so that the DOM of a cached route is never moved.
+#[derive(Default)]
+pub(crate) struct KeepAliveCache {
+    entries: Vec<CacheEntry>,
+    clock: u64,
+}
+
+impl KeepAliveCache {
+    /// Returns the key a route is cached with.
+    pub fn key(output: &RouteOutput) -> String {
+        let params: Vec<String> = output
+            .params
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+
+        format!("{}?{}", output.route.path, params.join("&"))
+    }
+
+    /// Records the `active` route and renders every cached route, hiding all but the active one.
+    pub fn render(
+        &mut self,
+        active: Option<(String, Html)>,
+        capacity: usize,
+        parent_active: bool,
+    ) -> Html {
+        let active_key = active.as_ref().map(|(key, _)| key.clone());
+
+        if let Some((key, html)) = active {
+            self.clock += 1;
+            match self.entries.iter_mut().find(|entry| entry.key == key) {
+                Some(entry) => {
+                    entry.html = html;
+                    entry.last_used = self.clock;
+                }
+                None => self.entries.push(CacheEntry {
+                    key,
+                    html,
+                    last_used: self.clock,
+                }),
+            }
+        }
+
+        while self.entries.len() > capacity.max(1) {
+            let lru = self
+                .entries
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(index, _)| index)
+                .expect("cache is not empty");
+            self.entries.remove(lru);
+        }
+
+        html! {
+            <>
+                { for self.entries.iter().map(|entry| {
+                    let active = active_key.as_ref() == Some(&entry.key);
+
+                    html! {
+                        <div key={entry.key.clone()} hidden={!active}>
+                            <ContextProvider<KeepAliveContext>
+                                context={KeepAliveContext { active: active && parent_active }}
+                            >
+                                { entry.html.clone() }
+                            </ContextProvider<KeepAliveContext>>
+                        </div>
+                    }
+                }) }
+            </>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(cache: &mut KeepAliveCache, key: &str, capacity: usize) -> Vec<String> {
+        cache.render(Some((key.to_string(), Html::default())), capacity, true);
+        cache
+            .entries
+            .iter()
+            .map(|entry| entry.key.clone())
+            .collect()
+    }
+
+    #[test]
+    fn least_recently_used_is_evicted_first() {
+        let mut cache = KeepAliveCache::default();
+
+        assert_eq!(visit(&mut cache, "a", 2), ["a"]);
+        assert_eq!(visit(&mut cache, "b", 2), ["a", "b"]);
+        assert_eq!(visit(&mut cache, "c", 2), ["b", "c"]);
+        assert_eq!(visit(&mut cache, "d", 2), ["c", "d"]);
+    }
+
+    #[test]
+    fn hit_refreshes_recency_without_moving() {
+        let mut cache = KeepAliveCache::default();
+
+        visit(&mut cache, "a", 2);
+        visit(&mut cache, "b", 2);
+        assert_eq!(visit(&mut cache, "a", 2), ["a", "b"]);
+        assert_eq!(visit(&mut cache, "c", 2), ["a", "c"]);
+    }
+
+    #[test]
+    fn capacity_zero_and_one_keep_the_active_route() {
+        for capacity in [0, 1] {
+            let mut cache = KeepAliveCache::default();
+
+            assert_eq!(visit(&mut cache, "a", capacity), ["a"]);
+            assert_eq!(visit(&mut cache, "b", capacity), ["b"]);
+            assert_eq!(visit(&mut cache, "b", capacity), ["b"]);
+        }
+    }
+
+    #[test]
+    fn no_match_keeps_the_cache() {
+        let mut cache = KeepAliveCache::default();
+
+        visit(&mut cache, "a", 2);
+        visit(&mut cache, "b", 2);
+        cache.render(None, 2, true);
+        assert_eq!(visit(&mut cache, "c", 2), ["b", "c"]);
+    }
+}
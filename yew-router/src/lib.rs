@@ -30,7 +30,7 @@
 //! fn secure() -> Html {
 //!     let navigator = use_navigator().unwrap();
 //!
-//!     let onclick_callback = Callback::from(move |_| navigator.push("../"));
+//!     let onclick_callback = Callback::from(move |_| navigator.report(navigator.push("../")));
 //!     html! {
 //!         <div>
 //!             <h1>{ "Secure" }</h1>
@@ -79,11 +79,26 @@
 
 extern crate self as yew_router;
 
+pub mod a11y;
+pub mod basename;
 pub mod components;
+#[cfg(feature = "devtools")]
+pub mod devtools;
+pub mod events;
+pub mod fragment;
+pub mod head;
 pub mod hooks;
+mod keep_alive;
+pub mod locale;
 pub mod navigator;
+pub mod outlets;
+pub mod redirects;
 pub mod router;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sitemap;
+pub mod state;
 pub mod switch;
+pub mod url;
 pub mod utils;
 
 pub use router::{BrowserRouter, HashRouter, Router};
@@ -103,11 +118,24 @@ pub mod prelude {
     //!
     //! This module re-exports the frequently used types from the crate.
 
-    pub use crate::components::{Link, Redirect};
+    pub use crate::basename::Basename;
+    pub use crate::components::{Authorize, Link, Outlet, Protected, Redirect, RouteTransition};
+    #[cfg(feature = "devtools")]
+    pub use crate::devtools::RouterDevtools;
+    pub use crate::events::{
+        LastNavigation, NavigationAction, NavigationAttempt, NavigationBlocker, NavigationEvent,
+        NavigationFuture, NavigationListener,
+    };
+    pub use crate::fragment::LocationExt;
+    pub use crate::head::RouteMeta;
     pub use crate::history::Location;
     pub use crate::hooks::*;
+    pub use crate::locale::Locales;
     pub use crate::navigator::{NavigationError, NavigationResult, Navigator};
-    pub use crate::switch::RouteOutput;
+    pub use crate::redirects::{RedirectKind, RouteRedirect};
+    pub use crate::state::LocationStateError;
+    pub use crate::switch::{CaseSensitivity, MatchPolicy, RouteOutput, TrailingSlash};
+    pub use crate::url::UrlBuilder;
     pub use crate::{BrowserRouter, HashRouter, Router, Switch};
     pub use nested_router::{Route, RouteList};
 }
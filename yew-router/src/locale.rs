@@ -0,0 +1,185 @@
+//! Locale prefixes and localized slugs.
+//!
+//! With [`Locales`] given to a router, every path starts with a locale segment, e.g. `/en/...`
+//! and `/de/...`. Routes are declared once with logical slugs and each locale may translate
+//! segments, so `/de/einstellungen` and `/en/settings` are both the logical path `/settings`.
+//!
+//! [`use_route_pathname`](crate::hooks::use_route_pathname) hands the logical path to a
+//! [`Switch`](crate::Switch), [`use_locale`](crate::hooks::use_locale) reports the active locale,
+//! and [`Navigator`](crate::navigator::Navigator) as well as [`Link`](crate::components::Link)
+//! turn logical routes into URLs of the active locale.
+
+use std::collections::BTreeMap;
+
+/// The locales of a router and their localized slugs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locales {
+    default: String,
+    locales: Vec<String>,
+    // locale -> logical segment -> localized segment
+    slugs: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Locales {
+    /// Creates locales with `default` used for paths without a locale segment.
+    pub fn new(default: impl Into<String>) -> Self {
+        let default = default.into();
+
+        Self {
+            locales: vec![default.clone()],
+            default,
+            slugs: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a locale.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        let locale = locale.into();
+        if !self.locales.contains(&locale) {
+            self.locales.push(locale);
+        }
+        self
+    }
+
+    /// Translates the path segment `logical` to `localized` in `locale`.
+    pub fn slug(
+        mut self,
+        locale: impl Into<String>,
+        logical: impl Into<String>,
+        localized: impl Into<String>,
+    ) -> Self {
+        self.slugs
+            .entry(locale.into())
+            .or_default()
+            .insert(logical.into(), localized.into());
+        self
+    }
+
+    /// Returns the locale used for paths without a locale segment.
+    pub fn default_locale(&self) -> &str {
+        &self.default
+    }
+
+    /// Returns all locales, the default one first.
+    pub fn locales(&self) -> &[String] {
+        &self.locales
+    }
+
+    /// Splits the locale segment off `path`, `None` if `path` does not start with one.
+    pub fn split<'a>(&self, path: &'a str) -> Option<(&str, &'a str)> {
+        let trimmed = path.strip_prefix('/').unwrap_or(path);
+        let (first, rest) = match trimmed.find('/') {
+            Some(index) => trimmed.split_at(index),
+            None => (trimmed, ""),
+        };
+
+        let locale = self.locales.iter().find(|locale| *locale == first)?;
+        Some((locale.as_str(), rest))
+    }
+
+    /// Returns the locale of `path`, the default locale if it has no locale segment.
+    pub fn locale_of(&self, path: &str) -> &str {
+        self.split(path)
+            .map(|(locale, _)| locale)
+            .unwrap_or(&self.default)
+    }
+
+    /// Turns a localized `path` into its locale and logical path.
+    pub fn to_logical(&self, path: &str) -> (String, String) {
+        let (locale, rest) = self.split(path).unwrap_or((&self.default, path));
+        let logical = self.translate(locale, rest, |slugs, segment| {
+            slugs
+                .iter()
+                .find(|(_, localized)| *localized == segment)
+                .map(|(logical, _)| logical.as_str())
+        });
+
+        (locale.to_string(), logical)
+    }
+
+    /// Turns an absolute logical `path` into a path of `locale`.
+    pub fn localize(&self, locale: &str, path: &str) -> String {
+        let localized = self.translate(locale, path, |slugs, segment| {
+            slugs.get(segment).map(|localized| localized.as_str())
+        });
+
+        if localized == "/" {
+            format!("/{}", locale)
+        } else {
+            format!("/{}{}", locale, localized)
+        }
+    }
+
+    fn translate<F>(&self, locale: &str, path: &str, lookup: F) -> String
+    where
+        F: for<'s> Fn(&'s BTreeMap<String, String>, &str) -> Option<&'s str>,
+    {
+        let slugs = self.slugs.get(locale);
+        let segments: Vec<&str> = path
+            .trim_start_matches('/')
+            .split('/')
+            .map(|segment| {
+                slugs
+                    .and_then(|slugs| lookup(slugs, segment))
+                    .unwrap_or(segment)
+            })
+            .collect();
+
+        format!("/{}", segments.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locales() -> Locales {
+        Locales::new("en")
+            .locale("de")
+            .slug("de", "settings", "einstellungen")
+    }
+
+    #[test]
+    fn split_locale_segment() {
+        let locales = locales();
+        assert_eq!(locales.locales(), ["en", "de"]);
+        assert_eq!(
+            locales.split("/de/einstellungen"),
+            Some(("de", "/einstellungen"))
+        );
+        assert_eq!(locales.split("/de"), Some(("de", "")));
+        assert_eq!(locales.split("/fr/settings"), None);
+        assert_eq!(locales.locale_of("/fr/settings"), "en");
+        assert_eq!(locales.locale_of("/de/einstellungen"), "de");
+    }
+
+    #[test]
+    fn to_logical_translates_slugs() {
+        let locales = locales();
+        assert_eq!(
+            locales.to_logical("/de/einstellungen"),
+            ("de".to_string(), "/settings".to_string())
+        );
+        assert_eq!(
+            locales.to_logical("/en/settings"),
+            ("en".to_string(), "/settings".to_string())
+        );
+        assert_eq!(
+            locales.to_logical("/settings"),
+            ("en".to_string(), "/settings".to_string())
+        );
+    }
+
+    #[test]
+    fn localize_round_trip() {
+        let locales = locales();
+        assert_eq!(locales.localize("de", "/settings"), "/de/einstellungen");
+        assert_eq!(locales.localize("de", "/"), "/de");
+        assert_eq!(locales.localize("en", "/settings"), "/en/settings");
+
+        for path in ["/settings", "/settings/profile", "/other"] {
+            let localized = locales.localize("de", path);
+            assert_eq!(locales.to_logical(&localized).1, path);
+        }
+    }
+}
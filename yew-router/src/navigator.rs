@@ -1,11 +1,66 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
 
 use serde::Serialize;
+use yew::Callback;
 
-use crate::history::{AnyHistory, History, HistoryError, HistoryResult};
+use crate::basename::Basename;
+use crate::components::return_to;
+use crate::events::{
+    LastNavigation, NavigationAction, NavigationAttempt, NavigationBlocker, NavigationEvent,
+    NavigationFuture, NavigationHub, NavigationListener,
+};
+use crate::history::{AnyHistory, History, Location};
+use crate::locale::Locales;
+use crate::outlets::set_outlet_path;
+use crate::state::{self, BackgroundState, LocationStateError, SerializedState};
+use crate::url::UrlBuilder;
+use crate::utils::encode_path;
 
-pub type NavigationError = HistoryError;
-pub type NavigationResult<T> = HistoryResult<T>;
+/// Why a navigation was not made.
+#[derive(Debug)]
+pub enum NavigationError {
+    /// A blocker registered with [`Navigator::block`] refused the navigation.
+    Blocked,
+    /// The target is the current location, so nothing was done.
+    Duplicate,
+    /// Another navigation happened before the awaited one was rendered.
+    Superseded,
+    /// The router is not set up for the navigation, e.g. it has no locales to switch between.
+    Unsupported(&'static str),
+    /// The query could not be serialized.
+    QuerySer(serde_urlencoded::ser::Error),
+    /// The state could not be serialized.
+    State(LocationStateError),
+}
+
+impl fmt::Display for NavigationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Blocked => write!(f, "navigation blocked"),
+            Self::Duplicate => write!(f, "navigation to the current location"),
+            Self::Superseded => write!(f, "navigation superseded by another one"),
+            Self::Unsupported(reason) => write!(f, "unsupported navigation: {}", reason),
+            Self::QuerySer(e) => write!(f, "failed to serialize query: {}", e),
+            Self::State(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for NavigationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Blocked | Self::Duplicate | Self::Superseded | Self::Unsupported(_) => None,
+            Self::QuerySer(e) => Some(e),
+            Self::State(e) => Some(e),
+        }
+    }
+}
+
+/// The result of a navigation, `Ok` if it was committed to the history.
+pub type NavigationResult<T> = Result<T, NavigationError>;
 
 /// The kind of Navigator Provider.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -22,61 +77,294 @@ pub enum NavigatorKind {
 #[derive(Debug, PartialEq, Clone)]
 pub struct Navigator {
     inner: AnyHistory,
-    basename: Option<String>,
+    basename: Option<Basename>,
+    hub: NavigationHub,
+    locales: Option<Rc<Locales>>,
+    on_error: Option<Callback<NavigationError>>,
+    // Navigations continue the current one instead of starting a new one.
+    redirect: bool,
 }
 
 impl Navigator {
-    pub(crate) fn new(history: AnyHistory, basename: Option<String>) -> Self {
+    pub(crate) fn new(
+        history: AnyHistory,
+        basename: Option<Basename>,
+        hub: NavigationHub,
+        locales: Option<Rc<Locales>>,
+        on_error: Option<Callback<NavigationError>>,
+    ) -> Self {
         Self {
             inner: history,
             basename,
+            hub,
+            locales,
+            on_error,
+            redirect: false,
+        }
+    }
+
+    /// Returns a navigator whose navigations are redirects of the current navigation, see
+    /// [`NavigationFuture`].
+    ///
+    /// Once the current navigation is rendered there is nothing to redirect, e.g. when a
+    /// [`Redirect`](crate::components::Redirect) is rendered by a state change, and its
+    /// navigations start new ones.
+    pub(crate) fn redirecting(&self) -> Self {
+        Self {
+            redirect: true,
+            ..self.clone()
         }
     }
 
     /// Returns basename of current navigator.
+    ///
+    /// This is the pattern the router was given, `:name` segments included. See
+    /// [`resolved_basename`](Self::resolved_basename) for the basename of the current location.
     pub fn basename(&self) -> Option<&str> {
-        self.basename.as_deref()
+        self.basename.as_ref().map(Basename::pattern)
+    }
+
+    /// Returns the basename resolved against the current location, with the `:name` segments
+    /// filled in. `None` if the location does not start with it.
+    pub fn resolved_basename(&self) -> Option<String> {
+        let location = self.inner.location();
+        Some(self.basename.as_ref()?.resolve(location.path())?.prefix)
+    }
+
+    /// Returns the values of the `:name` segments of the basename in the current location.
+    pub fn basename_params(&self) -> BTreeMap<String, String> {
+        let location = self.inner.location();
+        self.basename
+            .as_ref()
+            .and_then(|basename| basename.resolve(location.path()))
+            .map(|resolved| resolved.params)
+            .unwrap_or_default()
+    }
+
+    /// Pushes the current route under the basename with `params` substituted.
+    ///
+    /// Params that are not given keep their current value, so a tenant is switched with
+    /// `navigator.push_basename(&BTreeMap::from([("tenant".into(), "acme".into())]))` without
+    /// reloading the application.
+    ///
+    /// Without a basename, or with a param that has neither a current nor a given value, this is
+    /// reported as [`NavigationError::Unsupported`].
+    pub fn push_basename(&self, params: &BTreeMap<String, String>) -> NavigationResult<()> {
+        let mut merged = self.basename_params();
+        merged.extend(params.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        let prefix = self
+            .basename
+            .as_ref()
+            .ok_or(NavigationError::Unsupported("the router has no basename"))?
+            .fill(&merged)
+            .ok_or(NavigationError::Unsupported(
+                "a basename param has no value",
+            ))?;
+
+        let location = self.inner.location();
+        let route = self.strip_basename(location.path());
+        let route = if route == "/" && !prefix.is_empty() {
+            String::new()
+        } else {
+            route.into_owned()
+        };
+        let url = format!(
+            "{}{}{}{}",
+            prefix,
+            route,
+            location.query_str(),
+            location.hash()
+        );
+
+        self.navigate(NavigationAction::Push, url, false, |url| {
+            self.inner.push(url)
+        })
+    }
+
+    /// Returns the [`Locales`] of the router.
+    pub fn locales(&self) -> Option<&Locales> {
+        self.locales.as_deref()
+    }
+
+    /// Returns the locale of the current location, `None` if the router has no [`Locales`].
+    pub fn locale(&self) -> Option<String> {
+        let locales = self.locales()?;
+        let location = self.inner.location();
+
+        Some(
+            locales
+                .locale_of(&self.strip_basename(location.path()))
+                .to_string(),
+        )
+    }
+
+    /// Pushes the current page in `locale`.
+    ///
+    /// Without [`Locales`] this is reported as [`NavigationError::Unsupported`].
+    pub fn push_locale(&self, locale: &str) -> NavigationResult<()> {
+        let locales = self
+            .locales()
+            .ok_or(NavigationError::Unsupported("the router has no locales"))?;
+        let location = self.inner.location();
+        let (_, logical) = locales.to_logical(&self.strip_basename(location.path()));
+        let route = format!(
+            "{}{}{}",
+            locales.localize(locale, &logical),
+            location.query_str(),
+            location.hash()
+        );
+
+        self.push(&route)
     }
 
     /// Navigate back 1 page.
-    pub fn back(&self) {
-        self.go(-1);
+    pub fn back(&self) -> NavigationResult<()> {
+        self.go(-1)
     }
 
     /// Navigate forward 1 page.
-    pub fn forward(&self) {
-        self.go(1);
+    pub fn forward(&self) -> NavigationResult<()> {
+        self.go(1)
     }
 
     /// Navigate to a specific page with a `delta` relative to current page.
     ///
     /// See: <https://developer.mozilla.org/en-US/docs/Web/API/History/go>
-    pub fn go(&self, delta: isize) {
+    pub fn go(&self, delta: isize) -> NavigationResult<()> {
+        let _span =
+            tracing::debug_span!("navigate", action = ?NavigationAction::Pop, delta).entered();
+
+        let attempt = NavigationAttempt {
+            action: NavigationAction::Pop,
+            to: None,
+            delta: Some(delta),
+        };
+        if self.hub.is_blocked(&attempt) {
+            tracing::info!("navigation blocked");
+            return Err(NavigationError::Blocked);
+        }
+
+        self.hub.begin();
+        tracing::debug!("going through the history");
+        // The history reports the change asynchronously, the action is taken by the router then.
+        self.hub.set_pending(LastNavigation {
+            action: NavigationAction::Pop,
+            delta: Some(delta),
+            redirect: false,
+        });
         self.inner.go(delta);
+        Ok(())
     }
 
-    pub fn push<'a>(&self, route_s: &'a str) {
-        self.inner.push(self.prefix_basename(&route_s));
+    pub fn push<'a>(&self, route_s: &'a str) -> NavigationResult<()> {
+        let url = self.prefix_basename(route_s)?.into_owned();
+        self.navigate(NavigationAction::Push, url, false, |url| {
+            self.inner.push(url)
+        })
     }
 
-    pub fn replace<'a>(&self, route_s: &'a str) {
-        self.inner.replace(self.prefix_basename(&route_s));
+    pub fn replace<'a>(&self, route_s: &'a str) -> NavigationResult<()> {
+        let url = self.prefix_basename(route_s)?.into_owned();
+        self.navigate(NavigationAction::Replace, url, false, |url| {
+            self.inner.replace(url)
+        })
     }
 
-    pub fn push_with_state<'a, T>(&self, route_s: &'a str, state: T)
+    pub fn push_with_state<'a, T>(&self, route_s: &'a str, state: T) -> NavigationResult<()>
     where
         T: 'static,
     {
-        self.inner
-            .push_with_state(self.prefix_basename(&route_s), state);
+        let url = self.prefix_basename(route_s)?.into_owned();
+        self.navigate(NavigationAction::Push, url, true, |url| {
+            self.inner.push_with_state(url, state)
+        })
     }
 
-    pub fn replace_with_state<'a, T>(&self, route_s: &'a str, state: T)
+    pub fn replace_with_state<'a, T>(&self, route_s: &'a str, state: T) -> NavigationResult<()>
     where
         T: 'static,
     {
-        self.inner
-            .replace_with_state(self.prefix_basename(&route_s), state);
+        let url = self.prefix_basename(route_s)?.into_owned();
+        self.navigate(NavigationAction::Replace, url, true, |url| {
+            self.inner.replace_with_state(url, state)
+        })
+    }
+
+    /// Same as `.push_with_state()` but the state is serialized, see the [`state`](crate::state)
+    /// module.
+    pub fn push_with_serde_state<'a, T>(&self, route_s: &'a str, state: &T) -> NavigationResult<()>
+    where
+        T: Serialize,
+    {
+        let json = state::serialize(state).map_err(NavigationError::State)?;
+        self.push_with_state(route_s, SerializedState(json.clone()))?;
+        state::persist(self.kind(), &json);
+        Ok(())
+    }
+
+    /// Same as `.replace_with_state()` but the state is serialized, see the
+    /// [`state`](crate::state) module.
+    pub fn replace_with_serde_state<'a, T>(
+        &self,
+        route_s: &'a str,
+        state: &T,
+    ) -> NavigationResult<()>
+    where
+        T: Serialize,
+    {
+        let json = state::serialize(state).map_err(NavigationError::State)?;
+        self.replace_with_state(route_s, SerializedState(json.clone()))?;
+        state::persist(self.kind(), &json);
+        Ok(())
+    }
+
+    /// Replaces the current entry with the route a [`Protected`](crate::components::Protected)
+    /// redirected away from, or with `fallback` if there is none.
+    ///
+    /// Call it on the login route once the user is logged in.
+    pub fn resume_after_login<'a>(&self, fallback: &'a str) -> NavigationResult<()> {
+        let location = self.inner.location();
+        match return_to(location.query_str()) {
+            Some(route) => self.replace(&route),
+            None => self.replace(fallback),
+        }
+    }
+
+    /// Pushes the current location with the named outlet `name` showing `path`.
+    ///
+    /// `None` closes the outlet. The path and the other outlets are kept, see the
+    /// [`outlets`](crate::outlets) module.
+    pub fn push_outlet(&self, name: &str, path: Option<&str>) -> NavigationResult<()> {
+        self.push(&self.outlet_route(name, path))
+    }
+
+    /// Same as `.push_outlet()` but replaces the current history entry.
+    pub fn replace_outlet(&self, name: &str, path: Option<&str>) -> NavigationResult<()> {
+        self.replace(&self.outlet_route(name, path))
+    }
+
+    fn outlet_route(&self, name: &str, path: Option<&str>) -> String {
+        let location = self.inner.location();
+        let query = set_outlet_path(location.query_str(), name, path);
+        let pathname = self.strip_basename(location.path());
+
+        if query.is_empty() {
+            format!("{}{}", pathname, location.hash())
+        } else {
+            format!("{}?{}{}", pathname, query, location.hash())
+        }
+    }
+
+    /// Same as `.push()` but the route is a modal rendered over `background`.
+    ///
+    /// See [`use_background_location`](crate::hooks::use_background_location).
+    pub fn push_with_background<'a>(
+        &self,
+        route_s: &'a str,
+        background: Location,
+    ) -> NavigationResult<()> {
+        self.push_with_state(route_s, BackgroundState(background))
     }
 
     /// Same as `.push()` but affix the queries to the end of the route.
@@ -84,8 +372,10 @@ impl Navigator {
     where
         Q: Serialize,
     {
-        self.inner
-            .push_with_query(self.prefix_basename(&route_s), query)
+        let url = self.location_for(&UrlBuilder::new(route_s).query(query)?)?;
+        self.navigate(NavigationAction::Push, url, false, |url| {
+            self.inner.push(url)
+        })
     }
 
     /// Same as `.replace()` but affix the queries to the end of the route.
@@ -93,8 +383,10 @@ impl Navigator {
     where
         Q: Serialize,
     {
-        self.inner
-            .replace_with_query(self.prefix_basename(&route_s), query)
+        let url = self.location_for(&UrlBuilder::new(route_s).query(query)?)?;
+        self.navigate(NavigationAction::Replace, url, false, |url| {
+            self.inner.replace(url)
+        })
     }
 
     /// Same as `.push_with_state()` but affix the queries to the end of the route.
@@ -108,8 +400,10 @@ impl Navigator {
         Q: Serialize,
         T: 'static,
     {
-        self.inner
-            .push_with_query_and_state(self.prefix_basename(&route_s), query, state)
+        let url = self.location_for(&UrlBuilder::new(route_s).query(query)?)?;
+        self.navigate(NavigationAction::Push, url, true, |url| {
+            self.inner.push_with_state(url, state)
+        })
     }
 
     /// Same as `.replace_with_state()` but affix the queries to the end of the route.
@@ -123,8 +417,74 @@ impl Navigator {
         Q: Serialize,
         T: 'static,
     {
-        self.inner
-            .replace_with_query_and_state(self.prefix_basename(&route_s), query, state)
+        let url = self.location_for(&UrlBuilder::new(route_s).query(query)?)?;
+        self.navigate(NavigationAction::Replace, url, true, |url| {
+            self.inner.replace_with_state(url, state)
+        })
+    }
+
+    /// Same as `.push()` but returns a future resolving once the new route is rendered.
+    ///
+    /// See [`NavigationFuture`] for when it resolves.
+    pub fn push_async(&self, route_s: &str) -> NavigationFuture {
+        self.wait_for(|| self.push(route_s))
+    }
+
+    /// Same as `.replace()` but returns a future resolving once the new route is rendered.
+    pub fn replace_async(&self, route_s: &str) -> NavigationFuture {
+        self.wait_for(|| self.replace(route_s))
+    }
+
+    /// Same as `.go()` but returns a future resolving once the new route is rendered.
+    ///
+    /// The history does not report a `delta` past the first or last entry, so in that case the
+    /// future only resolves once another navigation supersedes it.
+    pub fn go_async(&self, delta: isize) -> NavigationFuture {
+        self.wait_for(|| self.go(delta))
+    }
+
+    fn wait_for(&self, f: impl FnOnce() -> NavigationResult<()>) -> NavigationFuture {
+        match f() {
+            Ok(()) => self.hub.wait(),
+            Err(e) => NavigationFuture::ready(Err(e)),
+        }
+    }
+
+    /// Registers a callback that is called after each committed navigation.
+    ///
+    /// The callback is called once the router has rendered the new location and stays registered
+    /// as long as the returned [`NavigationListener`] is alive.
+    pub fn listen<CB>(&self, callback: CB) -> NavigationListener
+    where
+        CB: Fn(&NavigationEvent) + 'static,
+    {
+        self.hub.listen(callback)
+    }
+
+    /// Registers a callback that can refuse navigations made by navigators by returning `true`.
+    ///
+    /// Refused navigations fail with [`NavigationError::Blocked`]. The blocker stays registered
+    /// as long as the returned [`NavigationBlocker`] is alive. Navigations started by the browser,
+    /// e.g. with the back button, can not be blocked.
+    pub fn block<CB>(&self, callback: CB) -> NavigationBlocker
+    where
+        CB: Fn(&NavigationAttempt) -> bool + 'static,
+    {
+        self.hub.block(callback)
+    }
+
+    /// Hands the error of a navigation to the `on_navigation_error` callback of the router.
+    ///
+    /// Without a callback the error is logged. A [`NavigationError::Duplicate`] is only logged.
+    pub fn report(&self, result: NavigationResult<()>) {
+        match (result, &self.on_error) {
+            (Ok(()), _) => {}
+            (Err(NavigationError::Duplicate), _) => {
+                tracing::debug!(error = %NavigationError::Duplicate, "navigation skipped")
+            }
+            (Err(e), Some(on_error)) => on_error.emit(e),
+            (Err(e), None) => tracing::warn!(error = %e, "navigation failed"),
+        }
     }
 
     /// Returns the Navigator kind.
@@ -136,8 +496,79 @@ impl Navigator {
         }
     }
 
-    pub(crate) fn prefix_basename<'a>(&self, route_s: &'a str) -> Cow<'a, str> {
-        match self.basename() {
+    pub(crate) fn hub(&self) -> &NavigationHub {
+        &self.hub
+    }
+
+    /// Hands `url` to the history call `f` unless it is blocked or, for navigations without
+    /// state, the current location.
+    fn navigate(
+        &self,
+        action: NavigationAction,
+        url: String,
+        with_state: bool,
+        f: impl FnOnce(String),
+    ) -> NavigationResult<()> {
+        let _span = tracing::debug_span!("navigate", action = ?action, to = %url).entered();
+
+        if !with_state && self.is_current(&url) {
+            tracing::debug!("navigation to the current location skipped");
+            return Err(NavigationError::Duplicate);
+        }
+
+        let attempt = NavigationAttempt {
+            action,
+            to: Some(url),
+            delta: None,
+        };
+        if self.hub.is_blocked(&attempt) {
+            tracing::info!("navigation blocked");
+            return Err(NavigationError::Blocked);
+        }
+
+        let url = attempt.to.unwrap_or_default();
+        let redirect = self.redirect && self.hub.is_navigating();
+        if redirect {
+            tracing::debug!("continuing the current navigation");
+            self.hub.continue_with(&url);
+        } else {
+            self.hub.begin();
+        }
+        self.commit(action, redirect, || f(url));
+        tracing::debug!("navigation committed");
+        Ok(())
+    }
+
+    fn is_current(&self, url: &str) -> bool {
+        let location = self.inner.location();
+        let current = format!(
+            "{}{}{}",
+            location.path(),
+            location.query_str(),
+            location.hash()
+        );
+
+        url == current
+    }
+
+    /// Runs a history call that notifies the router synchronously, tagged with `action`.
+    fn commit<R>(&self, action: NavigationAction, redirect: bool, f: impl FnOnce() -> R) -> R {
+        self.hub.set_pending(LastNavigation {
+            redirect,
+            ..LastNavigation::new(action)
+        });
+        let result = f();
+        // Nothing is left to take if the history refused the change.
+        self.hub.clear_pending();
+        result
+    }
+
+    pub(crate) fn prefix_basename<'a>(&self, route_s: &'a str) -> NavigationResult<Cow<'a, str>> {
+        let route_s = match self.localize(route_s) {
+            Some(localized) => Cow::from(encode_path(&localized).into_owned()),
+            None => encode_path(route_s),
+        };
+        Ok(match self.prefix()? {
             Some(base) => {
                 if route_s.is_empty() && route_s.is_empty() {
                     Cow::from("/")
@@ -145,25 +576,242 @@ impl Navigator {
                     Cow::from(format!("{}{}", base, route_s))
                 }
             }
-            None => route_s.into(),
+            None => route_s,
+        })
+    }
+
+    /// Returns the prefix of the routes navigated to.
+    ///
+    /// Outside of a parameterized basename, e.g. on a login page next to the tenants, its values
+    /// are unknown and this is reported as [`NavigationError::Unsupported`]; such a page
+    /// navigates into the basename with [`push_basename`](Self::push_basename).
+    fn prefix(&self) -> NavigationResult<Option<String>> {
+        let basename = match &self.basename {
+            Some(basename) => basename,
+            None => return Ok(None),
+        };
+        if !basename.is_dynamic() {
+            return Ok(Some(basename.pattern().to_string()));
+        }
+
+        match self.resolved_basename() {
+            Some(prefix) => Ok(Some(prefix)),
+            None => {
+                tracing::debug!(
+                    basename = %basename.pattern(),
+                    "location outside of the parameterized basename"
+                );
+                Err(NavigationError::Unsupported(
+                    "the location is outside of the parameterized basename",
+                ))
+            }
+        }
+    }
+
+    /// Returns the `href` of `url` for an element other than [`Link`](crate::components::Link).
+    ///
+    /// The locale and basename of the router are applied to the path, unless the builder has its
+    /// own [`basename`](UrlBuilder::basename), which is kept as is to point outside of the router.
+    /// A hash router gets the leading `#`.
+    ///
+    /// Outside of a parameterized basename this fails like [`push`](Self::push).
+    pub fn href_for(&self, url: &UrlBuilder) -> NavigationResult<String> {
+        let mut href = self.location_for(url)?;
+        if self.kind() == NavigatorKind::Hash {
+            href.insert(0, '#');
         }
+        Ok(href)
     }
 
-    // pub(crate) fn strip_basename<'a>(&self, path: Cow<'a, str>) -> Cow<'a, str> {
-    //     match self.basename() {
-    //         Some(m) => {
-    //             let mut path = path
-    //                 .strip_prefix(m)
-    //                 .map(|m| Cow::from(m.to_owned()))
-    //                 .unwrap_or(path);
+    /// Returns the location the history is given for `url`.
+    fn location_for(&self, url: &UrlBuilder) -> NavigationResult<String> {
+        if url.has_basename() {
+            return Ok(url.build());
+        }
 
-    //             if !path.starts_with('/') {
-    //                 path = format!("/{}", m).into();
-    //             }
+        let path = self.prefix_basename(&url.path())?.into_owned();
+        Ok(url.clone().map_path(|_| path).build())
+    }
 
-    //             path
-    //         }
-    //         None => path,
-    //     }
-    // }
+    /// Turns an absolute logical route into a route of the active locale.
+    ///
+    /// Relative routes and routes that already start with a locale are left as is.
+    fn localize(&self, route_s: &str) -> Option<String> {
+        let locales = self.locales()?;
+        let split = route_s
+            .find(|c| c == '?' || c == '#')
+            .unwrap_or(route_s.len());
+        let (path, suffix) = route_s.split_at(split);
+        if !path.starts_with('/') || locales.split(path).is_some() {
+            return None;
+        }
+
+        let locale = self.locale()?;
+
+        Some(format!("{}{}", locales.localize(&locale, path), suffix))
+    }
+
+    /// Removes the basename from a pathname read from the history.
+    ///
+    /// The returned path always starts with `/`.
+    pub(crate) fn strip_basename<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let resolved = self
+            .basename
+            .as_ref()
+            .and_then(|basename| basename.resolve(path));
+        let stripped = match resolved {
+            Some(resolved) => &path[resolved.prefix.len()..],
+            None => path,
+        };
+
+        if stripped.starts_with('/') {
+            stripped.into()
+        } else {
+            format!("/{}", stripped).into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::MemoryHistory;
+
+    use super::*;
+
+    fn navigator() -> Navigator {
+        Navigator::new(
+            AnyHistory::Memory(MemoryHistory::new()),
+            None,
+            NavigationHub::default(),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn push_to_the_current_location_is_a_duplicate() {
+        let navigator = navigator();
+
+        navigator.push("/a").unwrap();
+        assert!(matches!(
+            navigator.push("/a"),
+            Err(NavigationError::Duplicate)
+        ));
+        assert!(matches!(
+            navigator.replace("/a"),
+            Err(NavigationError::Duplicate)
+        ));
+        // A new state is a change even at the same location.
+        navigator.push_with_state("/a", 1).unwrap();
+    }
+
+    #[test]
+    fn blockers_veto_navigations() {
+        let navigator = navigator();
+        let attempts = Rc::new(std::cell::RefCell::new(Vec::new()));
+
+        let blocker = {
+            let attempts = attempts.clone();
+            navigator.block(move |attempt| {
+                attempts.borrow_mut().push(attempt.clone());
+                attempt.to.as_deref() == Some("/admin")
+            })
+        };
+
+        assert!(matches!(
+            navigator.push("/admin"),
+            Err(NavigationError::Blocked)
+        ));
+        assert_eq!(navigator.inner.location().path(), "/");
+        navigator.replace("/home").unwrap();
+        assert_eq!(
+            *attempts.borrow(),
+            [
+                NavigationAttempt {
+                    action: NavigationAction::Push,
+                    to: Some("/admin".to_string()),
+                    delta: None,
+                },
+                NavigationAttempt {
+                    action: NavigationAction::Replace,
+                    to: Some("/home".to_string()),
+                    delta: None,
+                },
+            ]
+        );
+
+        drop(blocker);
+        navigator.push("/admin").unwrap();
+        assert_eq!(navigator.inner.location().path(), "/admin");
+    }
+
+    fn navigator_with_basename(basename: &str) -> Navigator {
+        Navigator {
+            basename: Some(Basename::new(basename)),
+            ..navigator()
+        }
+    }
+
+    #[test]
+    fn static_basename_prefixes_routes() {
+        let navigator = navigator_with_basename("/app");
+
+        navigator.push("/settings").unwrap();
+        assert_eq!(navigator.inner.location().path(), "/app/settings");
+        assert_eq!(navigator.href_for(&UrlBuilder::new("/")).unwrap(), "/app/");
+    }
+
+    #[test]
+    fn parameterized_basename_needs_a_location_inside_it() {
+        let navigator = navigator_with_basename("/t/:tenant");
+
+        // `/t/settings` would be read back as the tenant `settings`.
+        assert!(matches!(
+            navigator.push("/settings"),
+            Err(NavigationError::Unsupported(_))
+        ));
+        assert!(matches!(
+            navigator.href_for(&UrlBuilder::new("/settings")),
+            Err(NavigationError::Unsupported(_))
+        ));
+        assert_eq!(navigator.inner.location().path(), "/");
+
+        let params = BTreeMap::from([("tenant".to_string(), "acme".to_string())]);
+        navigator.push_basename(&params).unwrap();
+        navigator.push("/settings").unwrap();
+        assert_eq!(navigator.inner.location().path(), "/t/acme/settings");
+        assert_eq!(
+            navigator.href_for(&UrlBuilder::new("/users")).unwrap(),
+            "/t/acme/users"
+        );
+    }
+
+    #[test]
+    fn report_skips_duplicates() {
+        let errors = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let on_error = {
+            let errors = errors.clone();
+            Callback::from(move |e: NavigationError| errors.borrow_mut().push(e.to_string()))
+        };
+        let navigator = Navigator {
+            on_error: Some(on_error),
+            ..navigator()
+        };
+
+        navigator.report(Ok(()));
+        navigator.report(Err(NavigationError::Duplicate));
+        navigator.report(Err(NavigationError::Blocked));
+
+        assert_eq!(*errors.borrow(), ["navigation blocked"]);
+    }
+
+    #[test]
+    fn blocked_navigations_do_not_start_a_generation() {
+        let navigator = navigator();
+        let _blocker = navigator.block(|_| true);
+
+        let generation = navigator.hub.begin();
+        assert!(navigator.push("/a").is_err());
+        assert_eq!(navigator.hub.begin(), generation + 1);
+    }
 }
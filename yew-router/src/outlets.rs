@@ -0,0 +1,77 @@
+//! Named outlets: independently routed areas sharing one URL.
+//!
+//! The main route tree uses the path of the URL. Every named outlet stores its own path in a
+//! query parameter, `outlet.<name>`, so `/inbox?outlet.sidebar=contacts%2F42` renders `inbox` in
+//! the main [`Switch`](crate::Switch) and `contacts/42` in the one with `outlet="sidebar"`.
+//! [`Navigator::push_outlet`](crate::navigator::Navigator::push_outlet) updates one outlet and
+//! leaves the path and the other outlets as they are.
+
+/// The prefix of the query parameters holding the paths of named outlets.
+pub const OUTLET_QUERY_PREFIX: &str = "outlet.";
+
+fn query_pairs(query_str: &str) -> Vec<(String, String)> {
+    let query = query_str.strip_prefix('?').unwrap_or(query_str);
+    serde_urlencoded::from_str(query).unwrap_or_default()
+}
+
+/// Returns the path of the outlet `name` in `query_str`, `None` if the outlet is closed.
+pub fn outlet_path(query_str: &str, name: &str) -> Option<String> {
+    let key = format!("{}{}", OUTLET_QUERY_PREFIX, name);
+
+    query_pairs(query_str)
+        .into_iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, path)| path)
+}
+
+/// Returns `query_str` with the outlet `name` set to `path`, or removed if `path` is `None`.
+///
+/// The returned query has no leading `?`.
+pub fn set_outlet_path(query_str: &str, name: &str, path: Option<&str>) -> String {
+    let key = format!("{}{}", OUTLET_QUERY_PREFIX, name);
+    let mut pairs = query_pairs(query_str);
+
+    match (pairs.iter().position(|(k, _)| *k == key), path) {
+        (Some(index), Some(path)) => pairs[index].1 = path.to_string(),
+        (Some(index), None) => {
+            pairs.remove(index);
+        }
+        (None, Some(path)) => pairs.push((key, path.to_string())),
+        (None, None) => {}
+    }
+
+    serde_urlencoded::to_string(pairs).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outlet_path_reads_query() {
+        let query = "?tab=1&outlet.sidebar=contacts%2F42";
+        assert_eq!(
+            outlet_path(query, "sidebar"),
+            Some("contacts/42".to_string())
+        );
+        assert_eq!(outlet_path(query, "modal"), None);
+        assert_eq!(outlet_path("", "sidebar"), None);
+    }
+
+    #[test]
+    fn set_outlet_path_round_trip() {
+        let query = set_outlet_path("?tab=1", "sidebar", Some("contacts/grüße"));
+        assert_eq!(query, "tab=1&outlet.sidebar=contacts%2Fgr%C3%BC%C3%9Fe");
+        assert_eq!(
+            outlet_path(&query, "sidebar"),
+            Some("contacts/grüße".to_string())
+        );
+
+        let query = set_outlet_path(&query, "sidebar", Some("inbox"));
+        assert_eq!(query, "tab=1&outlet.sidebar=inbox");
+
+        let query = set_outlet_path(&query, "sidebar", None);
+        assert_eq!(query, "tab=1");
+        assert_eq!(set_outlet_path(&query, "modal", None), "tab=1");
+    }
+}
@@ -0,0 +1,170 @@
+//! Declarative redirects and aliases resolved by [`Switch`](crate::Switch) before rendering.
+//!
+//! [`resolve_redirect`] does not touch the DOM, so a server rendering the application can use it
+//! with the same entries to answer with an HTTP redirect instead.
+
+use nested_router::{Route, RouteList};
+
+use crate::switch::{match_route, CaseSensitivity, RouteOutput};
+use crate::utils::{decode_segment, fill_params};
+
+/// What happens when the `from` pattern of a [`RouteRedirect`] matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RedirectKind {
+    /// The URL is replaced with the target. Reported as `301 Moved Permanently`.
+    Permanent,
+    /// The URL is replaced with the target. Reported as `302 Found`.
+    #[default]
+    Temporary,
+    /// The target is rendered while the URL is kept as is.
+    Alias,
+}
+
+impl RedirectKind {
+    /// Returns the HTTP status code a server should answer with, `None` for an alias.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            Self::Permanent => Some(301),
+            Self::Temporary => Some(302),
+            Self::Alias => None,
+        }
+    }
+}
+
+/// A redirect from an old path to a new one.
+///
+/// Both paths are relative to the [`Switch`](crate::Switch) the entry is given to. `:name`
+/// segments of `to` are filled with the params matched by `from`, so `/old-path/:id` can be
+/// mapped to `/new/:id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteRedirect {
+    pub from: String,
+    pub to: String,
+    pub kind: RedirectKind,
+}
+
+impl RouteRedirect {
+    /// Creates a [`RedirectKind::Permanent`] redirect.
+    pub fn permanent(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self::new(from, to, RedirectKind::Permanent)
+    }
+
+    /// Creates a [`RedirectKind::Temporary`] redirect.
+    pub fn temporary(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self::new(from, to, RedirectKind::Temporary)
+    }
+
+    /// Creates a [`RedirectKind::Alias`].
+    pub fn alias(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self::new(from, to, RedirectKind::Alias)
+    }
+
+    fn new(from: impl Into<String>, to: impl Into<String>, kind: RedirectKind) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            kind,
+        }
+    }
+}
+
+/// A [`RouteRedirect`] applied to a pathname.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedRedirect {
+    /// The target with its params filled in.
+    pub path: String,
+    pub kind: RedirectKind,
+}
+
+/// Returns the first entry of `redirects` whose `from` pattern matches `pathname`.
+pub fn resolve_redirect(redirects: &[RouteRedirect], pathname: &str) -> Option<ResolvedRedirect> {
+    resolve_redirect_with_case(redirects, pathname, CaseSensitivity::Sensitive)
+}
+
+/// Same as [`resolve_redirect`] but the `from` patterns are compared with `case`, like the routes
+/// of a [`Switch`](crate::Switch) with that [`CaseSensitivity`].
+pub fn resolve_redirect_with_case(
+    redirects: &[RouteRedirect],
+    pathname: &str,
+    case: CaseSensitivity,
+) -> Option<ResolvedRedirect> {
+    if redirects.is_empty() {
+        return None;
+    }
+
+    let patterns = RouteList {
+        routes: redirects
+            .iter()
+            .map(|redirect| Route {
+                path: redirect.from.clone(),
+                has_sub_routes: false,
+            })
+            .collect(),
+    };
+
+    let RouteOutput { route, params, .. } = match_route(&patterns, pathname, case).ok()?;
+    let redirect = redirects
+        .iter()
+        .find(|redirect| redirect.from == route.path)?;
+
+    let params = params
+        .into_iter()
+        .map(|(name, value)| {
+            let value = decode_segment(&value).into_owned();
+            (name, value)
+        })
+        .collect();
+
+    Some(ResolvedRedirect {
+        path: fill_params(&redirect.to, &params),
+        kind: redirect.kind,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_codes() {
+        assert_eq!(RedirectKind::Permanent.status_code(), Some(301));
+        assert_eq!(RedirectKind::Temporary.status_code(), Some(302));
+        assert_eq!(RedirectKind::Alias.status_code(), None);
+    }
+
+    #[test]
+    fn resolve_redirect_fills_params() {
+        let redirects = [
+            RouteRedirect::permanent("old-path/:id", "new/:id"),
+            RouteRedirect::alias("home", ""),
+        ];
+
+        assert_eq!(
+            resolve_redirect(&redirects, "old-path/a%2Fb"),
+            Some(ResolvedRedirect {
+                path: "new/a%2Fb".to_string(),
+                kind: RedirectKind::Permanent,
+            })
+        );
+        assert_eq!(
+            resolve_redirect(&redirects, "home").map(|it| it.kind),
+            Some(RedirectKind::Alias)
+        );
+        assert_eq!(resolve_redirect(&redirects, "other"), None);
+        assert_eq!(resolve_redirect(&[], "home"), None);
+    }
+
+    #[test]
+    fn resolve_redirect_folds_case() {
+        let redirects = [RouteRedirect::temporary("Old-Path/:id", "new/:id")];
+
+        assert_eq!(resolve_redirect(&redirects, "old-path/Abc"), None);
+        assert_eq!(
+            resolve_redirect_with_case(&redirects, "old-path/Abc", CaseSensitivity::Insensitive),
+            Some(ResolvedRedirect {
+                path: "new/Abc".to_string(),
+                kind: RedirectKind::Temporary,
+            })
+        );
+    }
+}
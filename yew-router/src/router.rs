@@ -1,25 +1,59 @@
 //! Router Component.
+use std::cell::RefCell;
 use std::rc::Rc;
 
 use yew::prelude::*;
 use yew::virtual_dom::AttrValue;
 
+use crate::a11y::{self, LiveRegion};
+use crate::basename::Basename;
+use crate::events::{LastNavigation, NavigationAction, NavigationHub};
+use crate::fragment::{scroll_to_fragment, FragmentContext, LocationExt};
+use crate::head::apply_head;
 use crate::history::{AnyHistory, BrowserHistory, HashHistory, History, Location};
-use crate::navigator::Navigator;
-use crate::utils::{base_url, strip_slash_suffix};
+use crate::locale::Locales;
+use crate::navigator::{NavigationError, Navigator};
+use crate::switch::MatchPolicy;
+use crate::utils::base_url;
 
 /// Props for [`Router`].
 #[derive(Properties, PartialEq, Clone)]
 pub struct RouterProps {
     pub children: Children,
     pub history: AnyHistory,
+    /// The basename, may contain `:name` segments, see the [`basename`](crate::basename) module.
     #[prop_or_default]
     pub basename: Option<AttrValue>,
+    /// The [`MatchPolicy`] used by every [`Switch`](crate::Switch) under this router.
+    #[prop_or_default]
+    pub policy: MatchPolicy,
+    /// Template of the document title, `{}` is replaced by the title of the matched route.
+    #[prop_or_default]
+    pub title_template: Option<AttrValue>,
+    /// Locale prefixes and localized slugs, see the [`locale`](crate::locale) module.
+    #[prop_or_default]
+    pub locales: Option<Locales>,
+    /// Called with the errors of navigations made by [`Link`](crate::components::Link),
+    /// [`Redirect`](crate::components::Redirect) and [`Switch`](crate::Switch) redirects, and
+    /// those handed to [`Navigator::report`]. They are logged if unset.
+    ///
+    /// [`NavigationError::Duplicate`] is not handed over, clicking a link to the current page is
+    /// not an error.
+    #[prop_or_default]
+    pub on_navigation_error: Option<Callback<NavigationError>>,
+    /// Announce each new page in an ARIA live region, see the [`a11y`](crate::a11y) module.
+    #[prop_or_default]
+    pub announce: bool,
+    /// Selector of the element focused after each navigation, see the [`a11y`](crate::a11y)
+    /// module.
+    #[prop_or_default]
+    pub focus: Option<AttrValue>,
 }
 
 #[derive(Clone)]
 pub(crate) struct LocationContext {
     location: Location,
+    navigation: LastNavigation,
     // Counter to force update.
     ctr: u32,
 }
@@ -28,6 +62,14 @@ impl LocationContext {
     pub fn location(&self) -> Location {
         self.location.clone()
     }
+
+    pub fn navigation(&self) -> LastNavigation {
+        self.navigation
+    }
+
+    pub fn ctr(&self) -> u32 {
+        self.ctr
+    }
 }
 
 impl PartialEq for LocationContext {
@@ -37,11 +79,12 @@ impl PartialEq for LocationContext {
 }
 
 impl Reducible for LocationContext {
-    type Action = Location;
+    type Action = (Location, LastNavigation);
 
-    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+    fn reduce(self: Rc<Self>, (location, navigation): Self::Action) -> Rc<Self> {
         Self {
-            location: action,
+            location,
+            navigation,
             ctr: self.ctr + 1,
         }
         .into()
@@ -69,30 +112,77 @@ fn base_router(props: &RouterProps) -> Html {
         history,
         children,
         basename,
+        policy,
+        title_template,
+        locales,
+        on_navigation_error,
+        announce,
+        focus,
     } = props.clone();
 
     let loc_ctx = use_reducer(|| LocationContext {
         location: history.location(),
+        navigation: LastNavigation::new(NavigationAction::Pop),
         ctr: 0,
     });
+    let hub = use_state(NavigationHub::default);
+    // The fragment of the last navigation if it only changed the fragment, which is kept out of
+    // the location so that the routes are not re-rendered.
+    let fragment_change = use_state(|| None::<Option<String>>);
+    let announcement = use_state(|| (0, AttrValue::default()));
+    // Whether the current navigation has been announced and focused, redirects included. The
+    // page a user lands on is left as is.
+    let presented = use_mut_ref(|| true);
+    let base_title = use_mut_ref(|| None::<String>);
 
-    let basename = basename.map(|m| strip_slash_suffix(&m).to_string());
+    let basename = basename.map(|m| Basename::new(&m));
     let navi_ctx = NavigatorContext {
-        navigator: Navigator::new(history.clone(), basename),
+        navigator: Navigator::new(
+            history.clone(),
+            basename,
+            (*hub).clone(),
+            locales.map(Rc::new),
+            on_navigation_error,
+        ),
     };
+    let current_history = history.clone();
 
     {
         let loc_ctx_dispatcher = loc_ctx.dispatcher();
+        let hub = (*hub).clone();
+        let fragment_change = fragment_change.clone();
 
         use_effect_with_deps(
             move |history| {
                 let history = history.clone();
+                let current = RefCell::new(history.location());
                 // Force location update when history changes.
-                loc_ctx_dispatcher.dispatch(history.location());
+                loc_ctx_dispatcher.dispatch((history.location(), hub.take_pending()));
+                fragment_change.set(None);
 
                 let history_cb = {
                     let history = history.clone();
-                    move || loc_ctx_dispatcher.dispatch(history.location())
+                    move || {
+                        let location = history.location();
+                        let previous = current.replace(location.clone());
+
+                        // Only the fragment changed, so the routes stay as they are. Going back
+                        // and forth is left to the scroll restoration of the browser.
+                        if previous.same_page(&location) && previous.hash() != location.hash() {
+                            let navigation = hub.take_pending();
+                            fragment_change.set(Some(location.fragment()));
+                            hub.emit(location.clone(), navigation);
+                            if let (Some(fragment), false) = (
+                                location.fragment(),
+                                navigation.action == NavigationAction::Pop,
+                            ) {
+                                scroll_to_fragment(&fragment);
+                            }
+                        } else {
+                            fragment_change.set(None);
+                            loc_ctx_dispatcher.dispatch((location, hub.take_pending()));
+                        }
+                    }
                 };
 
                 let listener = history.listen(history_cb);
@@ -106,10 +196,96 @@ fn base_router(props: &RouterProps) -> Html {
         );
     }
 
+    {
+        let hub = (*hub).clone();
+        let announcement = announcement.clone();
+        let base_title = base_title.clone();
+        let history = current_history;
+
+        // Switches record their matches while rendering, so they are up to date here.
+        use_effect_with_deps(
+            move |loc_ctx| {
+                // The initial state is skipped as the location is dispatched again on mount.
+                if loc_ctx.ctr > 0 {
+                    let meta = hub.meta();
+                    // The head is left alone until a route has metadata. From then on it is
+                    // reset for routes without any, back to the title the document started with.
+                    if meta.is_some() && base_title.borrow().is_none() {
+                        *base_title.borrow_mut() = Some(gloo::utils::document().title());
+                    }
+                    if let Some(base_title) = &*base_title.borrow() {
+                        let meta = meta.clone().unwrap_or_default();
+                        apply_head(&meta, title_template.as_deref(), base_title);
+                    }
+                    hub.emit(loc_ctx.location(), loc_ctx.navigation);
+
+                    // Going back and forth is left to the scroll restoration of the browser,
+                    // except for the page a user lands on, which is only rendered now.
+                    let fragment = loc_ctx.location.fragment();
+                    let restored =
+                        loc_ctx.ctr > 1 && loc_ctx.navigation.action == NavigationAction::Pop;
+                    if let (Some(fragment), false) = (&fragment, restored) {
+                        scroll_to_fragment(fragment);
+                    }
+
+                    // A redirect continues the navigation it redirects, which is presented once.
+                    if loc_ctx.ctr > 1 && !loc_ctx.navigation.redirect {
+                        *presented.borrow_mut() = false;
+                    }
+                    // A location a redirect has already left is skipped.
+                    let current = history.location();
+                    let left = (current.path(), current.query_str(), current.hash())
+                        != (
+                            loc_ctx.location.path(),
+                            loc_ctx.location.query_str(),
+                            loc_ctx.location.hash(),
+                        );
+
+                    // The page a user lands on, or is redirected to from there, is not
+                    // announced and keeps the focus.
+                    if !*presented.borrow() && !left {
+                        *presented.borrow_mut() = true;
+                        // The head is up to date, so is the title the announcement falls back to.
+                        if announce {
+                            let message =
+                                a11y::announcement(meta.as_ref(), title_template.as_deref());
+                            announcement.set((loc_ctx.ctr, message.into()));
+                        }
+
+                        let focus = meta
+                            .and_then(|meta| meta.focus)
+                            .or_else(|| focus.map(|focus| focus.to_string()));
+                        // An anchor to scroll to takes precedence.
+                        if let (Some(selector), None) = (focus, fragment) {
+                            a11y::move_focus(&selector);
+                        }
+                    }
+                }
+
+                || {}
+            },
+            (*loc_ctx).clone(),
+        );
+    }
+
+    let frag_ctx = FragmentContext {
+        fragment: match &*fragment_change {
+            Some(fragment) => fragment.clone(),
+            None => loc_ctx.location.fragment(),
+        },
+    };
+
     html! {
         <ContextProvider<NavigatorContext> context={navi_ctx}>
             <ContextProvider<LocationContext> context={(*loc_ctx).clone()}>
-                {children}
+                <ContextProvider<MatchPolicy> context={policy}>
+                    <ContextProvider<FragmentContext> context={frag_ctx}>
+                        {children}
+                        if announce {
+                            <LiveRegion id={announcement.0} message={announcement.1.clone()} />
+                        }
+                    </ContextProvider<FragmentContext>>
+                </ContextProvider<MatchPolicy>>
             </ContextProvider<LocationContext>>
         </ContextProvider<NavigatorContext>>
     }
@@ -133,8 +309,33 @@ pub fn router(props: &RouterProps) -> Html {
 #[derive(Properties, PartialEq, Clone)]
 pub struct ConcreteRouterProps {
     pub children: Children,
+    /// The basename, may contain `:name` segments, see the [`basename`](crate::basename) module.
     #[prop_or_default]
     pub basename: Option<AttrValue>,
+    /// The [`MatchPolicy`] used by every [`Switch`](crate::Switch) under this router.
+    #[prop_or_default]
+    pub policy: MatchPolicy,
+    /// Template of the document title, `{}` is replaced by the title of the matched route.
+    #[prop_or_default]
+    pub title_template: Option<AttrValue>,
+    /// Locale prefixes and localized slugs, see the [`locale`](crate::locale) module.
+    #[prop_or_default]
+    pub locales: Option<Locales>,
+    /// Called with the errors of navigations made by [`Link`](crate::components::Link),
+    /// [`Redirect`](crate::components::Redirect) and [`Switch`](crate::Switch) redirects, and
+    /// those handed to [`Navigator::report`]. They are logged if unset.
+    ///
+    /// [`NavigationError::Duplicate`] is not handed over, clicking a link to the current page is
+    /// not an error.
+    #[prop_or_default]
+    pub on_navigation_error: Option<Callback<NavigationError>>,
+    /// Announce each new page in an ARIA live region, see the [`a11y`](crate::a11y) module.
+    #[prop_or_default]
+    pub announce: bool,
+    /// Selector of the element focused after each navigation, see the [`a11y`](crate::a11y)
+    /// module.
+    #[prop_or_default]
+    pub focus: Option<AttrValue>,
 }
 
 /// A [`Router`] that provides location information and navigator via [`BrowserHistory`].
@@ -148,14 +349,23 @@ pub struct ConcreteRouterProps {
 /// You may also specify a different basename with props.
 #[function_component(BrowserRouter)]
 pub fn browser_router(props: &ConcreteRouterProps) -> Html {
-    let ConcreteRouterProps { children, basename } = props.clone();
+    let ConcreteRouterProps {
+        children,
+        basename,
+        policy,
+        title_template,
+        locales,
+        on_navigation_error,
+        announce,
+        focus,
+    } = props.clone();
     let history = use_state(|| AnyHistory::from(BrowserHistory::new()));
 
     // We acknowledge based in `<base href="..." />`
     let basename = basename.map(|m| m.to_string()).or_else(base_url);
 
     html! {
-        <BaseRouter history={(*history).clone()} {basename}>
+        <BaseRouter history={(*history).clone()} {basename} {policy} {title_template} {locales} {on_navigation_error} {announce} {focus}>
             {children}
         </BaseRouter>
     }
@@ -171,11 +381,20 @@ pub fn browser_router(props: &ConcreteRouterProps) -> Html {
 /// Prefer [`BrowserRouter`] whenever possible and use this as a last resort.
 #[function_component(HashRouter)]
 pub fn hash_router(props: &ConcreteRouterProps) -> Html {
-    let ConcreteRouterProps { children, basename } = props.clone();
+    let ConcreteRouterProps {
+        children,
+        basename,
+        policy,
+        title_template,
+        locales,
+        on_navigation_error,
+        announce,
+        focus,
+    } = props.clone();
     let history = use_state(|| AnyHistory::from(HashHistory::new()));
 
     html! {
-        <BaseRouter history={(*history).clone()} {basename}>
+        <BaseRouter history={(*history).clone()} {basename} {policy} {title_template} {locales} {on_navigation_error} {announce} {focus}>
             {children}
         </BaseRouter>
     }
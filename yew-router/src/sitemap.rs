@@ -0,0 +1,383 @@
+//! Sitemap and route manifest generation, available on native targets.
+//!
+//! [`Sitemap`] walks a [`RouteList`] and the lists of its nested [`Switch`](crate::Switch)es,
+//! enumerates the static routes and expands the dynamic ones with the param values given for
+//! them. Catch-all routes, e.g. `*` for a 404 page, are not pages and are left out. The result is
+//! rendered as a `sitemap.xml` or as a JSON manifest of all routes.
+//!
+//! The routes are plain data, so the application can share the function building them with a
+//! build script, which lists `yew-router` under `[build-dependencies]`:
+//!
+//! ```no_run
+//! use yew_router::prelude::*;
+//! use yew_router::sitemap::Sitemap;
+//!
+//! fn routes() -> RouteList {
+//!     RouteList {
+//!         routes: vec![
+//!             Route {
+//!                 path: "".to_string(),
+//!                 has_sub_routes: false,
+//!             },
+//!             Route {
+//!                 path: "posts/:slug".to_string(),
+//!                 has_sub_routes: false,
+//!             },
+//!             Route {
+//!                 path: "*".to_string(),
+//!                 has_sub_routes: false,
+//!             },
+//!         ],
+//!     }
+//! }
+//!
+//! // build.rs
+//! fn main() {
+//!     let routes = routes();
+//!     let sitemap = Sitemap::new("https://example.com", &routes)
+//!         .params("/posts/:slug", [("slug", "hello-world")]);
+//!
+//!     let out_dir = std::env::var("OUT_DIR").unwrap();
+//!     std::fs::write(format!("{}/sitemap.xml", out_dir), sitemap.to_xml()).unwrap();
+//!     std::fs::write(format!("{}/routes.json", out_dir), sitemap.to_json().unwrap()).unwrap();
+//! }
+//! ```
+//!
+//! The same builder works in a test, e.g. to check that every page of the sitemap is matched by
+//! a route other than the catch-all one.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use nested_router::RouteList;
+use serde::Serialize;
+
+use crate::head::escape;
+use crate::utils::{encode_path, fill_params, join_path, strip_slash_suffix};
+
+/// A page of the sitemap.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SitemapEntry {
+    /// The full pattern of the route, including the patterns of its parent routes.
+    pub pattern: String,
+    /// The path of the page, percent-encoded.
+    pub path: String,
+    /// The param values the path was built with.
+    pub params: BTreeMap<String, String>,
+}
+
+/// A route of the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestRoute {
+    /// The full pattern of the route, including the patterns of its parent routes.
+    pub pattern: String,
+    /// The names of the `:name` segments of the pattern.
+    pub params: Vec<String>,
+    /// Whether the route has sub-routes that were not given to the builder.
+    pub has_sub_routes: bool,
+    /// The paths of the route, one per set of param values, none for a catch-all route.
+    pub paths: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    routes: &'a [ManifestRoute],
+}
+
+/// A builder of a sitemap and a route manifest.
+#[derive(Clone)]
+pub struct Sitemap<'a> {
+    base_url: String,
+    routes: &'a RouteList,
+    sub_routes: BTreeMap<String, &'a RouteList>,
+    params: BTreeMap<String, Vec<BTreeMap<String, String>>>,
+}
+
+impl<'a> Sitemap<'a> {
+    /// Creates a builder for the top-level `routes` of a site served at `base_url`, e.g.
+    /// `https://example.com`.
+    pub fn new(base_url: &str, routes: &'a RouteList) -> Self {
+        Self {
+            base_url: strip_slash_suffix(base_url).to_string(),
+            routes,
+            sub_routes: BTreeMap::new(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the routes of the [`Switch`](crate::Switch) nested in the route with the full
+    /// pattern `parent`.
+    pub fn sub_routes(mut self, parent: &str, routes: &'a RouteList) -> Self {
+        self.sub_routes.insert(normalize(parent), routes);
+        self
+    }
+
+    /// Adds a set of values for the `:name` segments of the route with the full pattern
+    /// `pattern`.
+    ///
+    /// Each set adds one page. Dynamic routes without a complete set are left out of the
+    /// sitemap, and so are catch-all routes whatever their params.
+    pub fn params<I, K, V>(mut self, pattern: &str, values: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let values = values
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+        self.params
+            .entry(normalize(pattern))
+            .or_default()
+            .push(values);
+        self
+    }
+
+    /// Returns the routes of all lists, parents before their sub-routes.
+    pub fn manifest(&self) -> Vec<ManifestRoute> {
+        let mut routes = Vec::new();
+        self.walk("/", self.routes, &mut routes);
+        routes.into_iter().map(|(route, _)| route).collect()
+    }
+
+    /// Returns the pages of the sitemap, without duplicates.
+    pub fn entries(&self) -> Vec<SitemapEntry> {
+        let mut seen = BTreeSet::new();
+        let mut entries = Vec::new();
+
+        let mut routes = Vec::new();
+        self.walk("/", self.routes, &mut routes);
+
+        for (route, expanded) in routes {
+            // Parents are pages themselves only if their sub-routes are unknown.
+            if expanded {
+                continue;
+            }
+
+            for params in self.param_sets(&route) {
+                let path = page_path(&route.pattern, &params);
+                if seen.insert(path.clone()) {
+                    entries.push(SitemapEntry {
+                        pattern: route.pattern.clone(),
+                        path,
+                        params,
+                    });
+                }
+            }
+        }
+
+        entries
+    }
+
+    /// Renders the sitemap as XML.
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for entry in self.entries() {
+            xml.push_str(&format!(
+                "  <url><loc>{}{}</loc></url>\n",
+                escape(&self.base_url),
+                escape(&entry.path)
+            ));
+        }
+        xml.push_str("</urlset>\n");
+
+        xml
+    }
+
+    /// Renders the manifest as JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let routes = self.manifest();
+        serde_json::to_string_pretty(&Manifest { routes: &routes })
+    }
+
+    /// Collects the routes of `routes` and their sub-routes, and whether their sub-routes were
+    /// walked.
+    fn walk(&self, prefix: &str, routes: &RouteList, out: &mut Vec<(ManifestRoute, bool)>) {
+        for route in &routes.routes {
+            let pattern = normalize(&join_path(prefix, &route.path));
+            let params: Vec<String> = pattern
+                .split('/')
+                .filter_map(|segment| segment.strip_prefix(':'))
+                .map(str::to_string)
+                .collect();
+            let sub_routes = self
+                .sub_routes
+                .get(&pattern)
+                .copied()
+                .filter(|_| route.has_sub_routes);
+
+            let mut manifest_route = ManifestRoute {
+                has_sub_routes: route.has_sub_routes && sub_routes.is_none(),
+                pattern: pattern.clone(),
+                params,
+                paths: Vec::new(),
+            };
+            manifest_route.paths = self
+                .param_sets(&manifest_route)
+                .iter()
+                .map(|params| page_path(&pattern, params))
+                .collect();
+            out.push((manifest_route, sub_routes.is_some()));
+
+            if let Some(sub_routes) = sub_routes {
+                self.walk(&pattern, sub_routes, out);
+            }
+        }
+    }
+
+    /// Returns the complete param sets of `route`, a single empty one for a static route and
+    /// none for a catch-all route.
+    fn param_sets(&self, route: &ManifestRoute) -> Vec<BTreeMap<String, String>> {
+        if is_catch_all(&route.pattern) {
+            return Vec::new();
+        }
+        if route.params.is_empty() {
+            return vec![BTreeMap::new()];
+        }
+
+        self.params
+            .get(&route.pattern)
+            .map(|sets| {
+                sets.iter()
+                    .filter(|set| route.params.iter().all(|name| set.contains_key(name)))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Returns whether `pattern` has a `*` segment, which matches any path.
+fn is_catch_all(pattern: &str) -> bool {
+    pattern.split('/').any(|segment| segment.starts_with('*'))
+}
+
+/// Returns `pattern` with a leading and without a trailing slash.
+fn normalize(pattern: &str) -> String {
+    let pattern = strip_slash_suffix(pattern);
+    if pattern.starts_with('/') {
+        pattern.to_string()
+    } else {
+        format!("/{}", pattern)
+    }
+}
+
+/// Fills the params of `pattern` and percent-encodes its static segments too.
+fn page_path(pattern: &str, params: &BTreeMap<String, String>) -> String {
+    encode_path(&fill_params(pattern, params)).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use nested_router::Route;
+
+    use super::*;
+
+    fn route(path: &str, has_sub_routes: bool) -> Route {
+        Route {
+            path: path.to_string(),
+            has_sub_routes,
+        }
+    }
+
+    fn main_routes() -> RouteList {
+        RouteList {
+            routes: vec![
+                route("", false),
+                route("posts/:slug", false),
+                route("settings", true),
+                route("*", false),
+            ],
+        }
+    }
+
+    fn settings_routes() -> RouteList {
+        RouteList {
+            routes: vec![route("", false), route("profile", false)],
+        }
+    }
+
+    fn paths(sitemap: &Sitemap<'_>) -> Vec<String> {
+        sitemap
+            .entries()
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect()
+    }
+
+    #[test]
+    fn entries_skip_catch_all_and_incomplete_params() {
+        let routes = main_routes();
+        let sitemap = Sitemap::new("https://example.com/", &routes);
+
+        assert_eq!(paths(&sitemap), ["/", "/settings"]);
+    }
+
+    #[test]
+    fn entries_expand_params_and_sub_routes() {
+        let routes = main_routes();
+        let settings = settings_routes();
+        let sitemap = Sitemap::new("https://example.com", &routes)
+            .sub_routes("settings", &settings)
+            .params("/posts/:slug", [("slug", "hello/world")])
+            .params("posts/:slug/", [("slug", "grüße")])
+            .params("/posts/:slug", [("other", "x")]);
+
+        assert_eq!(
+            paths(&sitemap),
+            [
+                "/",
+                "/posts/hello%2Fworld",
+                "/posts/gr%C3%BC%C3%9Fe",
+                "/settings",
+                "/settings/profile"
+            ]
+        );
+    }
+
+    #[test]
+    fn manifest_lists_all_routes() {
+        let routes = main_routes();
+        let sitemap = Sitemap::new("https://example.com", &routes);
+        let manifest = sitemap.manifest();
+
+        let patterns: Vec<_> = manifest.iter().map(|it| it.pattern.as_str()).collect();
+        assert_eq!(patterns, ["/", "/posts/:slug", "/settings", "/*"]);
+        assert_eq!(manifest[1].params, ["slug"]);
+        assert!(manifest[1].paths.is_empty());
+        assert!(manifest[2].has_sub_routes);
+        assert!(manifest[3].paths.is_empty());
+
+        let json: serde_json::Value = serde_json::from_str(&sitemap.to_json().unwrap()).unwrap();
+        assert_eq!(json["routes"][2]["pattern"], "/settings");
+    }
+
+    #[test]
+    fn xml_escapes_urls() {
+        let routes = RouteList {
+            routes: vec![route("a&b", false)],
+        };
+        let xml = Sitemap::new("https://example.com", &routes).to_xml();
+
+        assert!(
+            xml.contains("<loc>https://example.com/a&amp;b</loc>"),
+            "{xml}"
+        );
+        assert!(xml.starts_with("<?xml"));
+    }
+
+    #[test]
+    fn entries_encode_static_segments() {
+        let routes = RouteList {
+            routes: vec![route("about us/:name", false)],
+        };
+        let sitemap = Sitemap::new("https://example.com", &routes)
+            .params("about us/:name", [("name", "a b")]);
+
+        assert_eq!(paths(&sitemap), ["/about%20us/a%20b"]);
+        assert_eq!(sitemap.manifest()[0].paths, ["/about%20us/a%20b"]);
+    }
+}
@@ -0,0 +1,201 @@
+//! History state serialized as JSON.
+//!
+//! State passed to [`Navigator::push_with_state`](crate::navigator::Navigator::push_with_state)
+//! only lives in memory and is lost on reload. State passed to
+//! [`Navigator::push_with_serde_state`](crate::navigator::Navigator::push_with_serde_state) is
+//! serialized and, with a browser or hash history, also stored in `history.state`, so it survives
+//! reloads and session restore. It is read with
+//! [`use_location_state`](crate::hooks::use_location_state).
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+use crate::history::Location;
+use crate::navigator::NavigatorKind;
+
+/// The key the JSON is stored under in `history.state`.
+const STATE_KEY: &str = "yew_router_state";
+
+/// The location a modal route is rendered over.
+#[derive(Debug, Clone)]
+pub(crate) struct BackgroundState(pub Location);
+
+/// The serialized state kept in memory, read before `history.state`.
+#[derive(Debug, Clone)]
+pub(crate) struct SerializedState(pub String);
+
+/// The Error type for serialized location state.
+#[derive(Debug)]
+pub enum LocationStateError {
+    /// Failed to serialize state.
+    Serialize(serde_json::Error),
+    /// The stored state is not a valid `type_name`.
+    Deserialize {
+        type_name: &'static str,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for LocationStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serialize(e) => write!(f, "failed to serialize state: {}", e),
+            Self::Deserialize { type_name, source } => {
+                write!(f, "failed to read state as {}: {}", type_name, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocationStateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serialize(e) => Some(e),
+            Self::Deserialize { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Serializes `state` to the JSON kept for it.
+pub(crate) fn serialize<T>(state: &T) -> Result<String, LocationStateError>
+where
+    T: Serialize,
+{
+    serde_json::to_string(state).map_err(LocationStateError::Serialize)
+}
+
+/// Deserializes the state kept as `json`.
+pub(crate) fn deserialize<T>(json: &str) -> Result<T, LocationStateError>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_str(json).map_err(|source| LocationStateError::Deserialize {
+        type_name: std::any::type_name::<T>(),
+        source,
+    })
+}
+
+/// Stores `json` in the state of the current browser history entry.
+pub(crate) fn persist(kind: NavigatorKind, json: &str) {
+    if kind == NavigatorKind::Memory {
+        return;
+    }
+
+    let history = match gloo::utils::window().history() {
+        Ok(history) => history,
+        Err(_) => return,
+    };
+
+    // The state set by the history is extended, it holds the id of the entry.
+    let state = history.state().unwrap_or(JsValue::NULL);
+    let state = if state.is_object() {
+        state
+    } else {
+        js_sys::Object::new().into()
+    };
+
+    if js_sys::Reflect::set(
+        &state,
+        &JsValue::from_str(STATE_KEY),
+        &JsValue::from_str(json),
+    )
+    .is_ok()
+    {
+        let _ = history.replace_state(&state, "");
+    }
+}
+
+/// Reads the serialized state of `location`, which must be the current location.
+pub(crate) fn read<T>(
+    location: &Location,
+    kind: NavigatorKind,
+) -> Option<Result<T, LocationStateError>>
+where
+    T: DeserializeOwned,
+{
+    let json = match location.state::<SerializedState>() {
+        Some(state) => state.0.clone(),
+        None if kind != NavigatorKind::Memory => stored()?,
+        None => return None,
+    };
+
+    Some(deserialize(&json))
+}
+
+fn stored() -> Option<String> {
+    let state = gloo::utils::window().history().ok()?.state().ok()?;
+    js_sys::Reflect::get(&state, &JsValue::from_str(STATE_KEY))
+        .ok()?
+        .as_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::history::{History, MemoryHistory};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Filter {
+        page: u32,
+        q: String,
+    }
+
+    #[test]
+    fn round_trip() {
+        let filter = Filter {
+            page: 2,
+            q: "grüße".to_string(),
+        };
+
+        let json = serialize(&filter).unwrap();
+        assert_eq!(deserialize::<Filter>(&json).unwrap(), filter);
+    }
+
+    #[test]
+    fn type_mismatch_names_the_type() {
+        let json = serialize(&vec![1, 2]).unwrap();
+
+        match deserialize::<Filter>(&json) {
+            Err(LocationStateError::Deserialize { type_name, .. }) => {
+                assert!(type_name.ends_with("Filter"), "{type_name}");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn read_from_memory_history() {
+        let history = MemoryHistory::new();
+        assert!(read::<Filter>(&history.location(), NavigatorKind::Memory).is_none());
+
+        let json = serialize(&Filter {
+            page: 1,
+            q: String::new(),
+        })
+        .unwrap();
+        history.push_with_state("/a", SerializedState(json));
+        let location = history.location();
+
+        assert_eq!(
+            read::<Filter>(&location, NavigatorKind::Memory)
+                .unwrap()
+                .unwrap()
+                .page,
+            1
+        );
+        assert!(matches!(
+            read::<String>(&location, NavigatorKind::Memory),
+            Some(Err(LocationStateError::Deserialize { .. }))
+        ));
+
+        // State that is not serialized is not read.
+        history.push_with_state("/b", 1);
+        assert!(read::<Filter>(&history.location(), NavigatorKind::Memory).is_none());
+    }
+}
@@ -4,14 +4,109 @@ use std::collections::BTreeMap;
 
 use nested_router::{Route, RouteList};
 use yew::prelude::*;
+use yew::virtual_dom::AttrValue;
+
+use crate::components::{OutletContext, TransitionPhase};
+use crate::head::RouteMeta;
+use crate::hooks::{use_location, use_navigator, use_route_active};
+use crate::keep_alive::KeepAliveCache;
+use crate::outlets::outlet_path;
+use crate::redirects::{resolve_redirect_with_case, RedirectKind, ResolvedRedirect, RouteRedirect};
+use crate::utils::{decode_segment, join_path, strip_slash_suffix};
+
+/// How letter case is compared when matching a pathname.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseSensitivity {
+    /// `/Secure` and `/secure` are different paths.
+    #[default]
+    Sensitive,
+    /// ASCII letters of static segments are compared case-insensitively.
+    ///
+    /// Params keep the case they have in the pathname.
+    Insensitive,
+}
+
+/// How a trailing slash is treated when matching a pathname.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrailingSlash {
+    /// `/secure/` and `/secure` are different paths.
+    #[default]
+    Strict,
+    /// A trailing slash is ignored when matching.
+    Ignore,
+    /// A trailing slash is ignored when matching and removed from the URL with
+    /// [`Navigator::replace`](crate::navigator::Navigator::replace).
+    Redirect,
+}
+
+/// The policy a [`Switch`] matches its pathname with.
+///
+/// It is set for all switches with the `policy` prop of [`Router`](crate::Router),
+/// [`BrowserRouter`](crate::BrowserRouter) or [`HashRouter`](crate::HashRouter) and can be
+/// overridden per [`Switch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MatchPolicy {
+    pub case: CaseSensitivity,
+    pub trailing_slash: TrailingSlash,
+}
 
 /// Props for [`Switch`]
 #[derive(Properties, PartialEq, Clone)]
 pub struct SwitchProps {
     /// Callback which returns [`Html`] to be rendered for the current route.
     pub render: Callback<RouteOutput, Html>,
+    /// Callback which returns the layout wrapping the current route.
+    ///
+    /// The layout renders an [`Outlet`](crate::components::Outlet) where the output of `render`
+    /// appears. Layouts nest: a route rendering another switch with a layout gets its chrome
+    /// inside the outer one.
+    ///
+    /// When no route matches, nothing is rendered, not even the layout; a catch-all `*` route is
+    /// a route like any other and gets the layout.
+    #[prop_or_default]
+    pub layout: Option<Callback<RouteOutput, Html>>,
+    /// Layouts of single routes, keyed by [`Route::path`] like `meta`, used instead of `layout`.
+    #[prop_or_default]
+    pub layouts: BTreeMap<String, Callback<RouteOutput, Html>>,
     pub routes: RouteList,
+    /// The path to match, usually the current path or the `sub_path` of an outer switch.
+    ///
+    /// Ignored if `outlet` is set.
+    #[prop_or_default]
     pub pathname: String,
+    /// Route the named outlet with this name instead of `pathname`.
+    ///
+    /// Nothing is rendered while the outlet is closed. See the [`outlets`](crate::outlets) module.
+    #[prop_or_default]
+    pub outlet: Option<AttrValue>,
+    /// Overrides the [`MatchPolicy`] of the router.
+    #[prop_or_default]
+    pub policy: Option<MatchPolicy>,
+    /// Redirects and aliases resolved before `routes` are matched.
+    #[prop_or_default]
+    pub redirects: Vec<RouteRedirect>,
+    /// Document metadata of routes, keyed by [`Route::path`].
+    ///
+    /// See the [`head`](crate::head) module.
+    #[prop_or_default]
+    pub meta: BTreeMap<String, RouteMeta>,
+    /// Keeps up to this many rendered routes mounted, the active one included.
+    ///
+    /// Inactive routes are hidden instead of being destroyed, so they keep their state. Each
+    /// route and set of params is kept separately. Components learn whether their route is the
+    /// active one with [`use_route_active`](crate::hooks::use_route_active) and
+    /// [`use_route_activation`](crate::hooks::use_route_activation).
+    #[prop_or_default]
+    pub keep_alive: Option<usize>,
+}
+
+/// The [`Switch`] a component is rendered in.
+#[derive(Clone, PartialEq)]
+struct SwitchScope {
+    id: Option<u32>,
+    /// The logical path matched by this switch and the enclosing ones, which the `pathname` of a
+    /// nested switch is the rest of.
+    prefix: String,
 }
 
 /// A Switch that dispatches route among variants of a [`RouteList`].
@@ -22,36 +117,442 @@ pub struct SwitchProps {
 /// Otherwise `html! {}` is rendered and a message is logged to console
 /// stating that no route can be matched.
 /// See the [crate level document][crate] for more information.
+///
+/// # Encoding
+///
+/// Matching is done against the percent-encoded `pathname`, so an encoded slash (`%2F`) never
+/// splits a segment. Params are percent-decoded before they are handed to `render`, which means
+/// `a%2Fb` yields the param value `a/b`.
+///
+/// # Redirects
+///
+/// Entries of `redirects` are checked before `routes`. A redirect replaces the current history
+/// entry with its target, keeping the query and the fragment, and renders nothing in the meantime.
+/// An alias renders the route of its target without touching the URL.
+///
+/// Targets are relative to the switch: a nested switch redirects below the route its enclosing
+/// switch matched.
 #[function_component(Switch)]
 pub fn switch(props: &SwitchProps) -> Html {
-    let nested_router::RouteOutput {
+    let router_policy = use_context::<MatchPolicy>();
+    let exiting = use_context::<TransitionPhase>().map_or(false, |phase| phase.exiting);
+    let route_active = use_route_active();
+    // An outgoing or hidden switch keeps rendering its route but neither redirects nor reports
+    // matches.
+    let active = !exiting && route_active;
+    let navigator = use_navigator();
+    // The hub outlives the filter below, so an inactive switch can withdraw its match.
+    let hub = navigator.as_ref().map(|navigator| navigator.hub().clone());
+    // The switch only navigates to redirect, which continues the current navigation.
+    let navigator = navigator
+        .filter(|_| active)
+        .map(|navigator| navigator.redirecting());
+    let location = use_location();
+    let cache = use_mut_ref(KeepAliveCache::default);
+
+    let policy = props.policy.or(router_policy).unwrap_or_default();
+
+    let scope = use_context::<SwitchScope>();
+    let parent_id = scope.as_ref().and_then(|scope| scope.id);
+    // A top-level switch is handed the whole logical path.
+    let parent_prefix = scope.map_or_else(|| "/".to_string(), |scope| scope.prefix);
+    let switch_id = *use_state(|| hub.as_ref().map(|hub| hub.register_switch(parent_id)));
+    {
+        let hub = hub.clone();
+
+        use_effect_with_deps(
+            move |_| {
+                move || {
+                    if let (Some(hub), Some(id)) = (hub, switch_id) {
+                        hub.unregister_switch(id);
+                    }
+                }
+            },
+            (),
+        );
+    }
+    // Switches rendered by this one, in its routes or layouts, are registered as its children
+    // and match the rest of `prefix`.
+    let scoped = |html: Html, prefix: String| {
+        html! {
+            <ContextProvider<SwitchScope> context={SwitchScope { id: switch_id, prefix }}>
+                { html }
+            </ContextProvider<SwitchScope>>
+        }
+    };
+    // Records the matched route for navigation events and its metadata for the document, `None`
+    // if nothing is rendered or the switch is inactive.
+    let record_match = |output: Option<&RouteOutput>| {
+        if let (Some(hub), Some(id)) = (&hub, switch_id) {
+            let output = output.filter(|_| active);
+            let meta = output.and_then(|output| props.meta.get(&output.route.path).cloned());
+            hub.set_match(id, output.cloned());
+            hub.set_meta(id, meta);
+        }
+    };
+
+    let finish = |active: Option<(String, Html)>| match props.keep_alive {
+        Some(capacity) => cache.borrow_mut().render(active, capacity, route_active),
+        None => active.map(|(_, html)| html).unwrap_or_default(),
+    };
+
+    // The pathname of a named outlet comes from the query, `None` if the outlet is closed.
+    let source = match &props.outlet {
+        Some(name) => location
+            .as_ref()
+            .and_then(|location| outlet_path(location.query_str(), name)),
+        None => Some(props.pathname.clone()),
+    };
+    let source_pathname = source.as_deref().unwrap_or_default();
+    // A query or fragment left in the pathname is not part of the route.
+    let source_pathname = source_pathname
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .unwrap_or_default();
+
+    let has_trailing_slash = source_pathname.len() > 1 && source_pathname.ends_with('/');
+    let pathname = match policy.trailing_slash {
+        TrailingSlash::Strict => source_pathname,
+        TrailingSlash::Ignore | TrailingSlash::Redirect => strip_slash_suffix(source_pathname),
+    };
+
+    {
+        let redirect = policy.trailing_slash == TrailingSlash::Redirect && has_trailing_slash;
+        let navigator = navigator.clone();
+        let location = location.clone();
+        let outlet = props.outlet.clone();
+        // Re-run for every pathname, query and fragment, not only when the slash appears, so
+        // the redirect is not skipped when a slashed URL follows another one.
+        let deps = (
+            redirect,
+            source_pathname.to_string(),
+            location.as_ref().map(|location| {
+                (
+                    location.query_str().to_string(),
+                    location.hash().to_string(),
+                )
+            }),
+        );
+
+        use_effect_with_deps(
+            move |(redirect, source_pathname, _)| {
+                match (*redirect, navigator, location, outlet) {
+                    (true, Some(navigator), _, Some(outlet)) => {
+                        let canonical = strip_slash_suffix(source_pathname);
+                        navigator.report(navigator.replace_outlet(&outlet, Some(canonical)));
+                    }
+                    (true, Some(navigator), Some(location), None) => {
+                        let path = navigator.strip_basename(location.path());
+                        if path.len() > 1 && path.ends_with('/') {
+                            let canonical = format!(
+                                "{}{}{}",
+                                strip_slash_suffix(&path),
+                                location.query_str(),
+                                location.hash()
+                            );
+                            tracing::debug!(to = %canonical, "redirecting to the path without trailing slash");
+                            navigator.report(navigator.replace(&canonical));
+                        }
+                    }
+                    _ => {}
+                }
+
+                || {}
+            },
+            deps,
+        );
+    }
+
+    let redirect = resolve_redirect_with_case(&props.redirects, pathname, policy.case);
+
+    {
+        let target = redirect
+            .as_ref()
+            .filter(|redirect| redirect.kind != RedirectKind::Alias)
+            .map(|redirect| redirect.path.clone());
+        let outlet = props.outlet.clone();
+        // Re-run when the location changes under the same target, e.g. when the redirect was
+        // blocked and another URL redirects to the same place.
+        let deps = (
+            target,
+            parent_prefix.clone(),
+            location.as_ref().map(|location| {
+                format!(
+                    "{}{}{}",
+                    location.path(),
+                    location.query_str(),
+                    location.hash()
+                )
+            }),
+        );
+
+        use_effect_with_deps(
+            move |(target, prefix, _)| {
+                match (target, navigator, location, outlet) {
+                    (Some(target), Some(navigator), _, Some(outlet)) => {
+                        navigator.report(navigator.replace_outlet(&outlet, Some(target.as_str())));
+                    }
+                    (Some(target), Some(navigator), Some(location), None) => {
+                        // Targets are relative to this switch, whose pathname starts after the
+                        // path matched by the enclosing switches.
+                        let url = format!(
+                            "{}{}{}",
+                            join_path(prefix, target),
+                            location.query_str(),
+                            location.hash()
+                        );
+                        navigator.report(navigator.replace(&url));
+                    }
+                    _ => {}
+                }
+
+                || {}
+            },
+            deps,
+        );
+    }
+
+    let span = tracing::debug_span!(
+        "match_route",
+        pathname = %pathname,
+        outlet = ?props.outlet,
+        route = tracing::field::Empty,
+        outcome = tracing::field::Empty,
+    )
+    .entered();
+
+    if source.is_none() {
+        span.record("outcome", "outlet_closed");
+        record_match(None);
+        return scoped(finish(None), parent_prefix);
+    }
+
+    let pathname = match &redirect {
+        Some(ResolvedRedirect {
+            path,
+            kind: RedirectKind::Alias,
+        }) => {
+            tracing::debug!(to = %path, "matching alias");
+            path.as_str()
+        }
+        Some(ResolvedRedirect { path, kind }) => {
+            span.record("outcome", "redirected");
+            tracing::debug!(to = %path, kind = ?kind, "redirecting");
+            record_match(None);
+            return scoped(finish(None), parent_prefix);
+        }
+        None => pathname,
+    };
+
+    let RouteOutput {
         sub_path,
         route,
         params,
-    } = match props.routes.route(&props.pathname) {
+    } = match match_route(&props.routes, pathname, policy.case) {
         Ok(output) => output,
         Err(e) => match e {
             nested_router::Error::InvalidPath => {
-                tracing::warn!("Invalid path: {}", props.pathname);
-                return Html::default();
+                span.record("outcome", "invalid_path");
+                tracing::warn!(pathname = %source_pathname, "invalid path");
+                record_match(None);
+                return scoped(finish(None), parent_prefix);
             }
             nested_router::Error::NotFound => {
-                tracing::warn!("no route matched");
-                return Html::default();
+                span.record("outcome", "not_found");
+                tracing::warn!(pathname = %pathname, "no route matched");
+                record_match(None);
+                return scoped(finish(None), parent_prefix);
             }
         },
     };
 
-    props.render.emit(RouteOutput {
+    let params = params
+        .into_iter()
+        .map(|(name, value)| {
+            let value = decode_segment(&value).into_owned();
+            (name, value)
+        })
+        .collect();
+
+    let output = RouteOutput {
+        sub_path,
+        route,
+        params,
+    };
+    span.record("route", output.route.path.as_str());
+    span.record("outcome", "matched");
+    tracing::debug!(sub_path = %output.sub_path, params = ?output.params, "route matched");
+    record_match(Some(&output));
+
+    // The sub path is the tail of the matched pathname.
+    let matched = pathname
+        .strip_suffix(output.sub_path.as_str())
+        .unwrap_or(pathname);
+    let prefix = join_path(&parent_prefix, matched);
+
+    let key = KeepAliveCache::key(&output);
+    let layout = props
+        .layouts
+        .get(&output.route.path)
+        .or(props.layout.as_ref())
+        .map(|layout| layout.emit(output.clone()));
+    let html = finish(Some((key, props.render.emit(output))));
+
+    let html = match layout {
+        Some(layout) => html! {
+            <ContextProvider<OutletContext> context={OutletContext { html }}>
+                { layout }
+            </ContextProvider<OutletContext>>
+        },
+        None => html,
+    };
+
+    scoped(html, prefix)
+}
+
+pub(crate) fn match_route(
+    routes: &RouteList,
+    pathname: &str,
+    case: CaseSensitivity,
+) -> Result<RouteOutput, nested_router::Error> {
+    if case == CaseSensitivity::Sensitive {
+        let nested_router::RouteOutput {
+            sub_path,
+            route,
+            params,
+        } = routes.route(pathname)?;
+
+        return Ok(RouteOutput {
+            sub_path,
+            route: route.clone(),
+            params,
+        });
+    }
+
+    // Static segments of the patterns and the whole pathname are folded to lowercase. ASCII
+    // folding keeps byte offsets intact, so the sub path and params can be cut from the
+    // original pathname afterwards.
+    let folded = RouteList {
+        routes: routes
+            .routes
+            .iter()
+            .map(|route| Route {
+                path: fold_pattern(&route.path),
+                ..route.clone()
+            })
+            .collect(),
+    };
+    let folded_pathname = pathname.to_ascii_lowercase();
+
+    let nested_router::RouteOutput {
         sub_path,
-        route: route.clone(),
+        route,
+        params,
+    } = folded.route(&folded_pathname)?;
+
+    let index = folded
+        .routes
+        .iter()
+        .position(|it| std::ptr::eq(it, route))
+        .expect("matched route is part of the route list");
+    let route = routes.routes[index].clone();
+
+    let sub_path = if folded_pathname.ends_with(&sub_path) {
+        pathname[pathname.len() - sub_path.len()..].to_string()
+    } else {
+        sub_path
+    };
+
+    let pattern_segments: Vec<&str> = route.path.trim_start_matches('/').split('/').collect();
+    let path_segments: Vec<&str> = pathname.trim_start_matches('/').split('/').collect();
+    let params = params
+        .into_iter()
+        .map(|(name, value)| {
+            let value = pattern_segments
+                .iter()
+                .position(|segment| segment.strip_prefix(':') == Some(name.as_str()))
+                .and_then(|index| path_segments.get(index))
+                .map(|segment| segment.to_string())
+                .unwrap_or(value);
+            (name, value)
+        })
+        .collect();
+
+    Ok(RouteOutput {
+        sub_path,
+        route,
         params,
     })
 }
 
+fn fold_pattern(pattern: &str) -> String {
+    pattern
+        .split('/')
+        .map(|segment| {
+            if segment.starts_with(':') {
+                segment.to_string()
+            } else {
+                segment.to_ascii_lowercase()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct RouteOutput {
+    /// The unmatched rest of the path, still percent-encoded.
+    ///
+    /// Pass it as `pathname` to a nested [`Switch`].
     pub sub_path: String,
     pub route: Route,
+    /// Params of the matched route, percent-decoded.
     pub params: BTreeMap<String, String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes(paths: &[&str]) -> RouteList {
+        RouteList {
+            routes: paths
+                .iter()
+                .map(|path| Route {
+                    path: path.to_string(),
+                    has_sub_routes: false,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn fold_pattern_keeps_param_names() {
+        assert_eq!(fold_pattern("Users/:userId/Posts"), "users/:userId/posts");
+    }
+
+    #[test]
+    fn match_route_sensitive() {
+        let routes = routes(&["users/:id"]);
+
+        let output = match_route(&routes, "users/42", CaseSensitivity::Sensitive).unwrap();
+        assert_eq!(output.route.path, "users/:id");
+        assert_eq!(output.params.get("id").map(String::as_str), Some("42"));
+        assert!(match_route(&routes, "Users/42", CaseSensitivity::Sensitive).is_err());
+    }
+
+    #[test]
+    fn match_route_insensitive_keeps_param_case() {
+        let routes = routes(&["about", "Users/:name"]);
+
+        let output = match_route(&routes, "ABOUT", CaseSensitivity::Insensitive).unwrap();
+        assert_eq!(output.route.path, "about");
+
+        let output = match_route(&routes, "users/MixedCase", CaseSensitivity::Insensitive).unwrap();
+        assert_eq!(output.route.path, "Users/:name");
+        assert_eq!(
+            output.params.get("name").map(String::as_str),
+            Some("MixedCase")
+        );
+    }
+}
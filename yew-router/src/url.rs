@@ -0,0 +1,191 @@
+//! Building URLs for routes.
+//!
+//! [`UrlBuilder`] puts a path, its segments, a typed query and a fragment together with plain
+//! string handling, so it produces the same URL on every target. [`Link`](crate::components::Link)
+//! and the query methods of [`Navigator`](crate::navigator::Navigator) use it, and
+//! [`Navigator::href_for`](crate::navigator::Navigator::href_for) turns one into an `href` for
+//! elements other than [`Link`](crate::components::Link).
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::navigator::{NavigationError, NavigationResult};
+use crate::utils::{encode_fragment, encode_path, encode_segment, join_path};
+
+/// A builder of a URL made of an optional basename, a path, a query and a fragment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UrlBuilder {
+    basename: Option<String>,
+    path: String,
+    query: String,
+    fragment: Option<String>,
+}
+
+impl UrlBuilder {
+    /// Creates a builder for `path`.
+    ///
+    /// Characters that are not allowed in a path are percent-encoded, `/` is kept. A query or
+    /// fragment in `path` is kept and can be extended with the other methods.
+    pub fn new(path: &str) -> Self {
+        let (path, fragment) = match path.split_once('#') {
+            Some((path, fragment)) => (path, Some(encode_fragment(fragment).into_owned())),
+            None => (path, None),
+        };
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+
+        Self {
+            basename: None,
+            path: encode_path(path).into_owned(),
+            query: encode_path(query).into_owned(),
+            fragment,
+        }
+    }
+
+    /// Appends a path segment, percent-encoding it including any `/`.
+    pub fn segment(mut self, segment: &str) -> Self {
+        self.path = join_path(&self.path, &encode_segment(segment));
+        self
+    }
+
+    /// Appends the fields of `query` to the query.
+    pub fn query<Q>(mut self, query: &Q) -> NavigationResult<Self>
+    where
+        Q: Serialize,
+    {
+        let query = serde_urlencoded::to_string(query).map_err(NavigationError::QuerySer)?;
+        self.push_query(&query);
+        Ok(self)
+    }
+
+    /// Appends `name=value` to the query.
+    pub fn query_pair(mut self, name: &str, value: &str) -> Self {
+        let pair = form_urlencoded_pair(name, value);
+        self.push_query(&pair);
+        self
+    }
+
+    /// Sets the fragment, given without the leading `#`.
+    pub fn fragment(mut self, fragment: &str) -> Self {
+        self.fragment = Some(encode_fragment(fragment).into_owned());
+        self
+    }
+
+    /// Prefixes the path with `basename`.
+    ///
+    /// [`Navigator::href_for`](crate::navigator::Navigator::href_for) keeps this basename instead
+    /// of applying the one of the router.
+    pub fn basename(mut self, basename: Option<&str>) -> Self {
+        self.basename = basename.map(str::to_string);
+        self
+    }
+
+    /// Returns the path including the basename.
+    pub fn path(&self) -> String {
+        match &self.basename {
+            Some(base) if base.is_empty() && self.path.is_empty() => "/".to_string(),
+            Some(base) => format!("{}{}", base, self.path),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Returns the encoded query without the leading `?`.
+    pub fn query_str(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns the encoded fragment without the leading `#`.
+    pub fn fragment_str(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    /// Builds the URL.
+    pub fn build(&self) -> String {
+        self.to_string()
+    }
+
+    pub(crate) fn has_basename(&self) -> bool {
+        self.basename.is_some()
+    }
+
+    /// Replaces the path, which is already encoded, with `f(path)`.
+    pub(crate) fn map_path(mut self, f: impl FnOnce(&str) -> String) -> Self {
+        self.path = f(&self.path);
+        self
+    }
+
+    fn push_query(&mut self, query: &str) {
+        if query.is_empty() {
+            return;
+        }
+        if !self.query.is_empty() {
+            self.query.push('&');
+        }
+        self.query.push_str(query);
+    }
+}
+
+impl fmt::Display for UrlBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path())?;
+        if !self.query.is_empty() {
+            write!(f, "?{}", self.query)?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", fragment)?;
+        }
+        Ok(())
+    }
+}
+
+fn form_urlencoded_pair(name: &str, value: &str) -> String {
+    // A single pair always serializes.
+    serde_urlencoded::to_string([(name, value)]).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Query {
+        page: u32,
+        q: &'static str,
+    }
+
+    #[test]
+    fn new_splits_query_and_fragment() {
+        let url = UrlBuilder::new("/search?page=1#results");
+        assert_eq!(url.path(), "/search");
+        assert_eq!(url.query_str(), "page=1");
+        assert_eq!(url.fragment_str(), Some("results"));
+        assert_eq!(url.build(), "/search?page=1#results");
+    }
+
+    #[test]
+    fn segments_are_encoded() {
+        let url = UrlBuilder::new("/files")
+            .segment("a/b")
+            .segment("grüße 100%");
+        assert_eq!(url.build(), "/files/a%2Fb/gr%C3%BC%C3%9Fe%20100%25");
+    }
+
+    #[test]
+    fn query_is_appended() {
+        let url = UrlBuilder::new("/search?lang=en")
+            .query(&Query { page: 2, q: "a&b" })
+            .unwrap()
+            .query_pair("sort", "new first");
+        assert_eq!(url.build(), "/search?lang=en&page=2&q=a%26b&sort=new+first");
+    }
+
+    #[test]
+    fn basename_and_fragment() {
+        let url = UrlBuilder::new("/settings")
+            .basename(Some("/app"))
+            .fragment("a b");
+        assert_eq!(url.to_string(), "/app/settings#a%20b");
+
+        assert_eq!(UrlBuilder::new("").basename(Some("")).build(), "/");
+    }
+}
@@ -1,61 +1,280 @@
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
 
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use wasm_bindgen::JsCast;
 
+/// Characters escaped when a whole path is handed to the history.
+///
+/// `%` is left alone so that already encoded paths are not encoded twice.
+const PATH: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Characters escaped in a fragment.
+const FRAGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
+
+/// Characters escaped inside a single path segment.
+///
+/// `/` is escaped as `%2F` so that a param value can never introduce an extra segment.
+const SEGMENT: &AsciiSet = &PATH.add(b'%').add(b'/').add(b'?').add(b'#');
+
 pub(crate) fn strip_slash_suffix(path: &str) -> &str {
     path.strip_suffix('/').unwrap_or(path)
 }
 
-static BASE_URL_LOADED: std::sync::Once = std::sync::Once::new();
+/// Joins two paths with exactly one `/` between them.
+pub(crate) fn join_path(base: &str, path: &str) -> String {
+    format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+/// Resolves `target` against the path `base` the way a browser resolves a relative `href`.
+///
+/// An absolute `target` is returned as is. `.` and `..` segments are collapsed, but never past
+/// the root.
+pub fn resolve_path(base: &str, target: &str) -> String {
+    if target.starts_with('/') {
+        return target.to_string();
+    }
+
+    let split = target
+        .find(|c| c == '?' || c == '#')
+        .unwrap_or(target.len());
+    let (target_path, suffix) = target.split_at(split);
+
+    if target_path.is_empty() {
+        return format!("{}{}", base, suffix);
+    }
+
+    let mut segments: Vec<&str> = base.split('/').collect();
+    // The last segment of the base is replaced by the relative path.
+    segments.pop();
+
+    for segment in target_path.split('/') {
+        match segment {
+            "." => {}
+            ".." => {
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    if matches!(target_path.rsplit('/').next(), Some(".") | Some("..")) {
+        segments.push("");
+    }
+
+    let path = segments.join("/");
+    if path.starts_with('/') {
+        format!("{}{}", path, suffix)
+    } else {
+        format!("/{}{}", path, suffix)
+    }
+}
+
+/// Percent-encodes characters that are not allowed in a URL path.
+///
+/// Separators (`/`, `?`, `#`) and existing escapes are kept as is.
+pub fn encode_path(path: &str) -> Cow<'_, str> {
+    utf8_percent_encode(path, PATH).into()
+}
+
+/// Percent-encodes a value so that it can be used as a single path segment.
+pub fn encode_segment(segment: &str) -> Cow<'_, str> {
+    utf8_percent_encode(segment, SEGMENT).into()
+}
+
+/// Percent-encodes characters that are not allowed in a URL fragment.
+pub fn encode_fragment(fragment: &str) -> Cow<'_, str> {
+    utf8_percent_encode(fragment, FRAGMENT).into()
+}
+
+/// Percent-decodes a single path segment.
+///
+/// `%2F` is decoded to `/`. If the decoded bytes are not valid UTF-8, the segment is returned
+/// unchanged.
+pub fn decode_segment(segment: &str) -> Cow<'_, str> {
+    percent_decode_str(segment)
+        .decode_utf8()
+        .unwrap_or(Cow::Borrowed(segment))
+}
+
+/// Substitutes `:name` segments of `path` with the matching entries of `params`.
+///
+/// Values are percent-encoded with [`encode_segment`]. Segments without a matching param are
+/// kept as is.
+pub fn fill_params(path: &str, params: &BTreeMap<String, String>) -> String {
+    path.split('/')
+        .map(
+            |segment| match segment.strip_prefix(':').and_then(|name| params.get(name)) {
+                Some(value) => encode_segment(value),
+                None => Cow::Borrowed(segment),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// An error reported while discovering the base url.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BaseUrlError {
+    /// The `href` of the `<base>` element is not a valid URL.
+    InvalidUrl(String),
+}
+
+impl fmt::Display for BaseUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUrl(href) => write!(f, "invalid base url: {}", href),
+        }
+    }
+}
+
+impl std::error::Error for BaseUrlError {}
+
+/// A source of the base url a [`BrowserRouter`](crate::BrowserRouter) uses as basename when none
+/// is given.
+pub trait BaseUrlSource {
+    /// Returns the pathname of the base url, `None` if the application is served from the root.
+    fn base_url(&self) -> Result<Option<String>, BaseUrlError>;
+}
+
+/// Reads the base url from the `<base href="..." />` element of the document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DomBaseUrl;
+
+impl BaseUrlSource for DomBaseUrl {
+    fn base_url(&self) -> Result<Option<String>, BaseUrlError> {
+        match gloo::utils::document().query_selector("base[href]") {
+            Ok(Some(base)) => {
+                parse_base_href(&base.unchecked_into::<web_sys::HtmlBaseElement>().href())
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A fixed base url, e.g. for rendering outside of a browser.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaticBaseUrl(Option<String>);
+
+impl StaticBaseUrl {
+    /// Creates a source that always returns `href`, which is parsed like the `href` of a
+    /// `<base>` element.
+    pub fn new(href: Option<&str>) -> Result<Self, BaseUrlError> {
+        href.map(parse_base_href)
+            .transpose()
+            .map(|base| Self(base.flatten()))
+    }
+}
+
+impl BaseUrlSource for StaticBaseUrl {
+    fn base_url(&self) -> Result<Option<String>, BaseUrlError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Extracts the pathname of a base url.
+///
+/// `href` is either an absolute URL or an absolute path. The trailing slash is removed and a
+/// root pathname yields `None`.
+pub fn parse_base_href(href: &str) -> Result<Option<String>, BaseUrlError> {
+    let invalid = || BaseUrlError::InvalidUrl(href.to_string());
+
+    let path = match href.split_once("://") {
+        Some((scheme, rest)) => {
+            let scheme_valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+            let host_end = rest.find(|c| c == '/' || c == '?' || c == '#');
+            let host = &rest[..host_end.unwrap_or(rest.len())];
+            if !scheme_valid || host.is_empty() || host.contains(char::is_whitespace) {
+                return Err(invalid());
+            }
+
+            host_end.map_or("/", |end| &rest[end..])
+        }
+        None if href.starts_with('/') && !href.starts_with("//") => href,
+        None => return Err(invalid()),
+    };
+
+    let path = &path[..path.find(|c| c == '?' || c == '#').unwrap_or(path.len())];
+    let path = if path.is_empty() { "/" } else { path };
+
+    if path == "/" {
+        Ok(None)
+    } else {
+        Ok(Some(strip_slash_suffix(path).to_string()))
+    }
+}
+
 thread_local! {
-    static BASE_URL: RefCell<Option<String>> = RefCell::new(None);
+    static BASE_URL_SOURCE: RefCell<Rc<dyn BaseUrlSource>> = RefCell::new(Rc::new(DomBaseUrl));
+    // `None` until the base url is loaded.
+    static BASE_URL: RefCell<Option<Option<String>>> = RefCell::new(None);
+}
+
+/// Replaces the source [`base_url`] is read from and resets the cached value.
+pub fn set_base_url_source(source: impl BaseUrlSource + 'static) {
+    BASE_URL_SOURCE.with(|it| *it.borrow_mut() = Rc::new(source));
+    reset_base_url();
+}
+
+/// Resets the cached base url, so the next [`base_url`] call reads it from the source again.
+pub fn reset_base_url() {
+    BASE_URL.with(|it| *it.borrow_mut() = None);
 }
 
 // This exists so we can cache the base url. It costs us a `to_string` call instead of a DOM API
 // call. Considering base urls are generally short, it *should* be less expensive.
 pub fn base_url() -> Option<String> {
-    BASE_URL_LOADED.call_once(|| {
-        BASE_URL.with(|val| {
-            *val.borrow_mut() = fetch_base_url();
-        })
-    });
-    BASE_URL.with(|it| it.borrow().as_ref().map(|it| it.to_string()))
+    BASE_URL.with(|it| {
+        it.borrow_mut()
+            .get_or_insert_with(|| match fetch_base_url_checked() {
+                Ok(base) => {
+                    tracing::debug!(base_url = ?base, "loaded base url");
+                    base
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to load base url");
+                    None
+                }
+            })
+            .clone()
+    })
 }
 
+/// Reads the base url from the current source without caching it.
+///
+/// An invalid base url is treated as none, use [`fetch_base_url_checked`] to get the error.
 pub fn fetch_base_url() -> Option<String> {
-    match gloo::utils::document().query_selector("base[href]") {
-        Ok(Some(base)) => {
-            let base = base.unchecked_into::<web_sys::HtmlBaseElement>().href();
-
-            let url = web_sys::Url::new(&base).unwrap();
-            let base = url.pathname();
-
-            let base = if base != "/" {
-                strip_slash_suffix(&base)
-            } else {
-                return None;
-            };
+    fetch_base_url_checked().ok().flatten()
+}
 
-            Some(base.to_string())
-        }
-        _ => None,
-    }
+/// Reads the base url from the current source without caching it.
+pub fn fetch_base_url_checked() -> Result<Option<String>, BaseUrlError> {
+    let source = BASE_URL_SOURCE.with(|it| it.borrow().clone());
+    source.base_url()
 }
 
-#[cfg(target_arch = "wasm32")]
-pub fn compose_path(pathname: &str, query: &str) -> Option<String> {
-    gloo::utils::window()
-        .location()
-        .href()
-        .ok()
-        .and_then(|base| web_sys::Url::new_with_base(pathname, &base).ok())
-        .map(|url| {
-            url.set_search(query);
-            format!("{}{}", url.pathname(), url.search())
-        })
-}
-
-#[cfg(not(target_arch = "wasm32"))]
+/// Appends the encoded `query` to `pathname`.
+///
+/// See [`UrlBuilder`](crate::url::UrlBuilder) for building whole URLs.
 pub fn compose_path(pathname: &str, query: &str) -> Option<String> {
     let query = query.trim();
 
@@ -65,3 +284,143 @@ pub fn compose_path(pathname: &str, query: &str) -> Option<String> {
         Some(pathname.to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_round_trip() {
+        for value in [
+            "plain",
+            "a/b",
+            "100%",
+            "50%2F",
+            "a b?c#d",
+            "grüße",
+            "日本語/パス",
+        ] {
+            let encoded = encode_segment(value);
+            assert!(!encoded.contains('/'), "{encoded:?}");
+            assert_eq!(decode_segment(&encoded), value);
+        }
+    }
+
+    #[test]
+    fn encode_segment_escapes_separators_and_percent() {
+        assert_eq!(encode_segment("a/b"), "a%2Fb");
+        assert_eq!(encode_segment("100%"), "100%25");
+        assert_eq!(encode_segment("ü"), "%C3%BC");
+    }
+
+    #[test]
+    fn encode_path_keeps_separators_and_escapes() {
+        assert_eq!(encode_path("/a b/c?d=e#f"), "/a%20b/c?d=e#f");
+        assert_eq!(encode_path("/a%2Fb"), "/a%2Fb");
+        assert_eq!(encode_path("/grüße"), "/gr%C3%BC%C3%9Fe");
+    }
+
+    #[test]
+    fn decode_segment_keeps_invalid_utf8() {
+        assert_eq!(decode_segment("%FF"), "%FF");
+        assert_eq!(decode_segment("a%2Fb"), "a/b");
+    }
+
+    #[test]
+    fn fill_params_encodes_values() {
+        let params = BTreeMap::from([
+            ("id".to_string(), "a/b".to_string()),
+            ("name".to_string(), "grüße 100%".to_string()),
+        ]);
+
+        assert_eq!(
+            fill_params("/users/:id/:name", &params),
+            "/users/a%2Fb/gr%C3%BC%C3%9Fe%20100%25"
+        );
+        assert_eq!(fill_params("/users/:missing", &params), "/users/:missing");
+    }
+
+    #[test]
+    fn resolve_path_relative_targets() {
+        assert_eq!(resolve_path("/a/b/c", "/x"), "/x");
+        assert_eq!(resolve_path("/a/b/c", "d"), "/a/b/d");
+        assert_eq!(resolve_path("/a/b/c", "./d"), "/a/b/d");
+        assert_eq!(resolve_path("/a/b/c", "../d"), "/a/d");
+        assert_eq!(resolve_path("/a/b/c", ".."), "/a/");
+        assert_eq!(resolve_path("/a", "../../../d"), "/d");
+        assert_eq!(resolve_path("/a/b", "c?x=1#y"), "/a/c?x=1#y");
+        assert_eq!(resolve_path("/a/b", "?x=1"), "/a/b?x=1");
+    }
+
+    #[test]
+    fn parse_base_href_absolute_url() {
+        assert_eq!(
+            parse_base_href("https://example.com/app/").unwrap(),
+            Some("/app".to_string())
+        );
+        assert_eq!(
+            parse_base_href("https://example.com/app?x=1#top").unwrap(),
+            Some("/app".to_string())
+        );
+        assert_eq!(parse_base_href("https://example.com").unwrap(), None);
+        assert_eq!(parse_base_href("https://example.com/").unwrap(), None);
+    }
+
+    #[test]
+    fn parse_base_href_absolute_path() {
+        assert_eq!(parse_base_href("/app").unwrap(), Some("/app".to_string()));
+        assert_eq!(
+            parse_base_href("/app/nested/").unwrap(),
+            Some("/app/nested".to_string())
+        );
+        assert_eq!(parse_base_href("/").unwrap(), None);
+    }
+
+    #[test]
+    fn parse_base_href_rejects_relative_and_empty() {
+        for href in ["", "app/", "./app", "//example.com/app", "https:///app"] {
+            assert_eq!(
+                parse_base_href(href),
+                Err(BaseUrlError::InvalidUrl(href.to_string())),
+                "{href:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn base_url_reads_static_source() {
+        set_base_url_source(StaticBaseUrl::new(Some("https://example.com/app/")).unwrap());
+        assert_eq!(base_url(), Some("/app".to_string()));
+        assert_eq!(fetch_base_url(), Some("/app".to_string()));
+
+        set_base_url_source(StaticBaseUrl::new(None).unwrap());
+        assert_eq!(base_url(), None);
+    }
+
+    #[test]
+    fn base_url_is_cached_until_reset() {
+        struct Counting(Rc<std::cell::Cell<u32>>);
+
+        impl BaseUrlSource for Counting {
+            fn base_url(&self) -> Result<Option<String>, BaseUrlError> {
+                self.0.set(self.0.get() + 1);
+                Ok(Some("/counted".to_string()))
+            }
+        }
+
+        let calls = Rc::new(std::cell::Cell::new(0));
+        set_base_url_source(Counting(calls.clone()));
+        assert_eq!(base_url(), Some("/counted".to_string()));
+        assert_eq!(base_url(), Some("/counted".to_string()));
+        assert_eq!(calls.get(), 1);
+
+        reset_base_url();
+        assert_eq!(base_url(), Some("/counted".to_string()));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn static_base_url_rejects_invalid_href() {
+        assert!(StaticBaseUrl::new(Some("app")).is_err());
+    }
+}